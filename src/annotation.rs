@@ -0,0 +1,554 @@
+// shapes drawn over a screenshot, kept in its metadata record as a layer in image pixels and
+// only burnt into exported copies
+
+// the highlighter's own alpha, its color's alpha is ignored
+const HIGHLIGHT_ALPHA: u8 = 96;
+const ARROW_HEAD_ANGLE: f32 = 0.5;
+const ARROW_HEAD_MIN_LENGTH: f32 = 12.0;
+const ELLIPSE_SEGMENTS: usize = 96;
+// the callout number's height relative to its circle
+const CALLOUT_FONT_RATIO: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Arrow,
+    Rectangle,
+    Ellipse,
+    Freehand,
+    Text,
+    Callout,
+    Highlight,
+    Pixelate,
+    Blur,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 9] = [
+        Tool::Arrow,
+        Tool::Rectangle,
+        Tool::Ellipse,
+        Tool::Freehand,
+        Tool::Text,
+        Tool::Callout,
+        Tool::Highlight,
+        Tool::Pixelate,
+        Tool::Blur,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Tool::Arrow => "➡ Arrow",
+            Tool::Rectangle => "⬜ Rectangle",
+            Tool::Ellipse => "⭕ Ellipse",
+            Tool::Freehand => "✏ Freehand",
+            Tool::Text => "🔤 Text",
+            Tool::Callout => "🔢 Callout",
+            Tool::Highlight => "🖍 Highlight",
+            Tool::Pixelate => "🔳 Pixelate",
+            Tool::Blur => "💧 Blur",
+        }
+    }
+
+    // see `Annotation::size`
+    pub fn default_size(&self) -> f32 {
+        match self {
+            Tool::Arrow | Tool::Rectangle | Tool::Ellipse | Tool::Freehand => 4.0,
+            Tool::Text | Tool::Callout => 28.0,
+            Tool::Highlight => 0.0,
+            Tool::Pixelate => 16.0,
+            Tool::Blur => 8.0,
+        }
+    }
+
+    // placed with a click, every other tool is dragged
+    pub fn is_click(&self) -> bool {
+        matches!(self, Tool::Text | Tool::Callout)
+    }
+
+    // the shape of a finished click or drag, `None` when there is nothing to draw
+    pub fn shape(&self, points: &[[f32; 2]], text: &str, next_callout: u32) -> Option<Shape> {
+        let from = *points.first()?;
+        let to = *points.last()?;
+        let shape = match self {
+            Tool::Text if text.trim().is_empty() => return None,
+            Tool::Text => Shape::Text {
+                at: from,
+                text: text.to_string(),
+            },
+            Tool::Callout => Shape::Callout {
+                at: from,
+                number: next_callout,
+            },
+            Tool::Freehand if points.len() < 2 => return None,
+            Tool::Freehand => Shape::Freehand {
+                points: points.to_vec(),
+            },
+            _ if from == to => return None,
+            Tool::Arrow => Shape::Arrow { from, to },
+            Tool::Rectangle => Shape::Rectangle { from, to },
+            Tool::Ellipse => Shape::Ellipse { from, to },
+            Tool::Highlight => Shape::Highlight { from, to },
+            Tool::Pixelate => Shape::Pixelate { from, to },
+            Tool::Blur => Shape::Blur { from, to },
+        };
+        Some(shape)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Shape {
+    Arrow { from: [f32; 2], to: [f32; 2] },
+    // the remaining two-point shapes fill or outline the box between two opposite corners
+    Rectangle { from: [f32; 2], to: [f32; 2] },
+    Ellipse { from: [f32; 2], to: [f32; 2] },
+    Freehand { points: Vec<[f32; 2]> },
+    // `at` is the top left corner of the first line
+    Text { at: [f32; 2], text: String },
+    // a numbered circle centered on `at`
+    Callout { at: [f32; 2], number: u32 },
+    Highlight { from: [f32; 2], to: [f32; 2] },
+    Pixelate { from: [f32; 2], to: [f32; 2] },
+    Blur { from: [f32; 2], to: [f32; 2] },
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Annotation {
+    pub shape: Shape,
+    // unpremultiplied rgba, unused by pixelate and blur
+    pub color: [u8; 4],
+    // the stroke width, the font height of text, the diameter of a callout, the block size of
+    // pixelate or the sigma of blur, in image pixels
+    pub size: f32,
+}
+
+impl Shape {
+    // replaces every point of the shape by `map` of it
+    pub fn map_points(&mut self, map: impl Fn([f32; 2]) -> [f32; 2]) {
+        match self {
+            Shape::Arrow { from, to }
+            | Shape::Rectangle { from, to }
+            | Shape::Ellipse { from, to }
+            | Shape::Highlight { from, to }
+            | Shape::Pixelate { from, to }
+            | Shape::Blur { from, to } => {
+                *from = map(*from);
+                *to = map(*to);
+            }
+            Shape::Freehand { points } => {
+                for point in points {
+                    *point = map(*point);
+                }
+            }
+            Shape::Text { at, .. } | Shape::Callout { at, .. } => *at = map(*at),
+        }
+    }
+}
+
+impl Annotation {
+    // pixelate and blur change the pixels below them, so only a rendered copy can show them
+    pub fn is_effect(&self) -> bool {
+        matches!(self.shape, Shape::Pixelate { .. } | Shape::Blur { .. })
+    }
+
+    pub fn highlight_color(&self) -> [u8; 4] {
+        let [r, g, b, _] = self.color;
+        [r, g, b, HIGHLIGHT_ALPHA]
+    }
+
+    // the polylines this annotation strokes, shared by the UI overlay and `flatten`
+    pub fn strokes(&self) -> Vec<Vec<[f32; 2]>> {
+        match &self.shape {
+            Shape::Arrow { from, to } => {
+                let [left, right] = arrow_head(*from, *to, self.size);
+                vec![vec![*from, *to], vec![left, *to, right]]
+            }
+            Shape::Rectangle { from, to } => {
+                let ([left, top], [right, bottom]) = normalized(*from, *to);
+                vec![vec![
+                    [left, top],
+                    [right, top],
+                    [right, bottom],
+                    [left, bottom],
+                    [left, top],
+                ]]
+            }
+            Shape::Ellipse { from, to } => {
+                let ([left, top], [right, bottom]) = normalized(*from, *to);
+                let center = [(left + right) / 2.0, (top + bottom) / 2.0];
+                let radii = [(right - left) / 2.0, (bottom - top) / 2.0];
+                vec![(0..=ELLIPSE_SEGMENTS)
+                    .map(|segment| {
+                        let angle =
+                            segment as f32 / ELLIPSE_SEGMENTS as f32 * std::f32::consts::TAU;
+                        [
+                            center[0] + radii[0] * angle.cos(),
+                            center[1] + radii[1] * angle.sin(),
+                        ]
+                    })
+                    .collect()]
+            }
+            Shape::Freehand { points } => vec![points.clone()],
+            _ => Vec::new(),
+        }
+    }
+}
+
+// the ends of the two lines forming the head of an arrow pointing at `to`
+pub fn arrow_head(from: [f32; 2], to: [f32; 2], size: f32) -> [[f32; 2]; 2] {
+    let length = (size * 4.0).max(ARROW_HEAD_MIN_LENGTH);
+    let angle = (from[1] - to[1]).atan2(from[0] - to[0]);
+    [angle - ARROW_HEAD_ANGLE, angle + ARROW_HEAD_ANGLE]
+        .map(|angle| [to[0] + length * angle.cos(), to[1] + length * angle.sin()])
+}
+
+// the top left and bottom right corner of the box between two opposite corners
+pub fn normalized(from: [f32; 2], to: [f32; 2]) -> ([f32; 2], [f32; 2]) {
+    (
+        [from[0].min(to[0]), from[1].min(to[1])],
+        [from[0].max(to[0]), from[1].max(to[1])],
+    )
+}
+
+pub fn callout_font_size(size: f32) -> f32 {
+    size * CALLOUT_FONT_RATIO
+}
+
+// applies only pixelate and blur, the UI draws everything else over the result
+pub fn apply_effects(image_buffer: &mut image::RgbaImage, annotations: &[Annotation]) {
+    for annotation in annotations {
+        match annotation.shape {
+            Shape::Pixelate { from, to } => pixelate(image_buffer, from, to, annotation.size),
+            Shape::Blur { from, to } => blur(image_buffer, from, to, annotation.size),
+            _ => {}
+        }
+    }
+}
+
+// burns the whole layer into the image, effects first so shapes over a blurred area stay sharp
+pub fn flatten(image_buffer: &mut image::RgbaImage, annotations: &[Annotation]) {
+    apply_effects(image_buffer, annotations);
+    let font = font();
+    for annotation in annotations {
+        match &annotation.shape {
+            Shape::Highlight { from, to } => {
+                let (min, max) = normalized(*from, *to);
+                let mut coverage = Coverage::new(image_buffer, min, max);
+                coverage.fill_rect(min, max);
+                coverage.blend_onto(image_buffer, annotation.highlight_color());
+            }
+            Shape::Text { at, text } => draw_text(
+                image_buffer,
+                &font,
+                *at,
+                annotation.size,
+                text,
+                annotation.color,
+            ),
+            Shape::Callout { at, number } => {
+                let radius = annotation.size / 2.0;
+                let mut coverage = Coverage::new(
+                    image_buffer,
+                    [at[0] - radius, at[1] - radius],
+                    [at[0] + radius, at[1] + radius],
+                );
+                coverage.fill_circle(*at, radius);
+                coverage.blend_onto(image_buffer, annotation.color);
+                let font_size = callout_font_size(annotation.size);
+                let label = number.to_string();
+                let (width, height) = text_size(&font, font_size, &label);
+                draw_text(
+                    image_buffer,
+                    &font,
+                    [at[0] - width / 2.0, at[1] - height / 2.0],
+                    font_size,
+                    &label,
+                    contrasting(annotation.color),
+                );
+            }
+            _ => {
+                for stroke in annotation.strokes() {
+                    stroke_polyline(image_buffer, &stroke, annotation.size, annotation.color);
+                }
+            }
+        }
+    }
+}
+
+// white on dark colors and black on light ones
+pub fn contrasting(color: [u8; 4]) -> [u8; 4] {
+    let [r, g, b, _] = color.map(f32::from);
+    if 0.299 * r + 0.587 * g + 0.114 * b > 150.0 {
+        [0, 0, 0, 255]
+    } else {
+        [255, 255, 255, 255]
+    }
+}
+
+// the pixel box of the image inside the box between two opposite corners
+fn pixel_region(
+    image_buffer: &image::RgbaImage,
+    from: [f32; 2],
+    to: [f32; 2],
+) -> Option<(u32, u32, u32, u32)> {
+    let (min, max) = normalized(from, to);
+    let left = (min[0].floor().max(0.0) as u32).min(image_buffer.width());
+    let top = (min[1].floor().max(0.0) as u32).min(image_buffer.height());
+    let right = (max[0].ceil().max(0.0) as u32).min(image_buffer.width());
+    let bottom = (max[1].ceil().max(0.0) as u32).min(image_buffer.height());
+    (right > left && bottom > top).then_some((left, top, right - left, bottom - top))
+}
+
+// covers the pixels with an opaque color
+pub fn fill(image_buffer: &mut image::RgbaImage, from: [f32; 2], to: [f32; 2], color: [u8; 4]) {
+    let Some((left, top, width, height)) = pixel_region(image_buffer, from, to) else {
+        return;
+    };
+    for y in top..top + height {
+        for x in left..left + width {
+            image_buffer.put_pixel(x, y, image::Rgba(color));
+        }
+    }
+}
+
+pub fn pixelate(image_buffer: &mut image::RgbaImage, from: [f32; 2], to: [f32; 2], block: f32) {
+    let Some((left, top, width, height)) = pixel_region(image_buffer, from, to) else {
+        return;
+    };
+    let block = (block.round() as u32).max(1);
+    for block_top in (top..top + height).step_by(block as usize) {
+        for block_left in (left..left + width).step_by(block as usize) {
+            let block_right = block_left.saturating_add(block).min(left + width);
+            let block_bottom = block_top.saturating_add(block).min(top + height);
+            let mut sum = [0u64; 4];
+            for y in block_top..block_bottom {
+                for x in block_left..block_right {
+                    for (total, channel) in sum.iter_mut().zip(image_buffer.get_pixel(x, y).0) {
+                        *total += u64::from(channel);
+                    }
+                }
+            }
+            let count = u64::from((block_right - block_left) * (block_bottom - block_top));
+            let average = image::Rgba(sum.map(|total| (total / count) as u8));
+            for y in block_top..block_bottom {
+                for x in block_left..block_right {
+                    image_buffer.put_pixel(x, y, average);
+                }
+            }
+        }
+    }
+}
+
+pub fn blur(image_buffer: &mut image::RgbaImage, from: [f32; 2], to: [f32; 2], sigma: f32) {
+    let Some((left, top, width, height)) = pixel_region(image_buffer, from, to) else {
+        return;
+    };
+    let region = image::imageops::crop_imm(image_buffer, left, top, width, height).to_image();
+    let blurred = image::imageops::blur(&region, sigma.max(0.1));
+    image::imageops::replace(image_buffer, &blurred, i64::from(left), i64::from(top));
+}
+
+// how much of each pixel in a box a shape covers, so overlapping segments are blended once
+struct Coverage {
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+}
+
+impl Coverage {
+    // clipped to the image, `min` and `max` may lie outside of it
+    fn new(image_buffer: &image::RgbaImage, min: [f32; 2], max: [f32; 2]) -> Self {
+        let (left, top, width, height) = pixel_region(image_buffer, min, max).unwrap_or_default();
+        Self {
+            left,
+            top,
+            width,
+            height,
+            values: vec![0.0; (width * height) as usize],
+        }
+    }
+
+    // calls `coverage` with the center of every pixel in the box and keeps the largest value
+    fn cover(&mut self, min: [f32; 2], max: [f32; 2], coverage: impl Fn([f32; 2]) -> f32) {
+        let left = (min[0].floor().max(self.left as f32) as u32).max(self.left);
+        let top = (min[1].floor().max(self.top as f32) as u32).max(self.top);
+        let right = (max[0].ceil().max(0.0) as u32).min(self.left + self.width);
+        let bottom = (max[1].ceil().max(0.0) as u32).min(self.top + self.height);
+        for y in top..bottom {
+            for x in left..right {
+                let value = coverage([x as f32 + 0.5, y as f32 + 0.5]).clamp(0.0, 1.0);
+                let index = ((y - self.top) * self.width + (x - self.left)) as usize;
+                self.values[index] = self.values[index].max(value);
+            }
+        }
+    }
+
+    fn stroke_segment(&mut self, a: [f32; 2], b: [f32; 2], width: f32) {
+        let reach = width / 2.0 + 1.0;
+        let (min, max) = normalized(a, b);
+        self.cover(
+            [min[0] - reach, min[1] - reach],
+            [max[0] + reach, max[1] + reach],
+            |point| width / 2.0 + 0.5 - segment_distance(point, a, b),
+        );
+    }
+
+    fn fill_rect(&mut self, min: [f32; 2], max: [f32; 2]) {
+        self.cover(min, max, |[x, y]| {
+            let horizontal = (x + 0.5).min(max[0]) - (x - 0.5).max(min[0]);
+            let vertical = (y + 0.5).min(max[1]) - (y - 0.5).max(min[1]);
+            horizontal.clamp(0.0, 1.0) * vertical.clamp(0.0, 1.0)
+        });
+    }
+
+    fn fill_circle(&mut self, center: [f32; 2], radius: f32) {
+        self.cover(
+            [center[0] - radius - 1.0, center[1] - radius - 1.0],
+            [center[0] + radius + 1.0, center[1] + radius + 1.0],
+            |[x, y]| radius + 0.5 - (x - center[0]).hypot(y - center[1]),
+        );
+    }
+
+    fn blend_onto(&self, image_buffer: &mut image::RgbaImage, color: [u8; 4]) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let value = self.values[(y * self.width + x) as usize];
+                if value > 0.0 {
+                    blend(image_buffer, self.left + x, self.top + y, color, value);
+                }
+            }
+        }
+    }
+}
+
+fn segment_distance(point: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    let segment = [b[0] - a[0], b[1] - a[1]];
+    let length_squared = segment[0] * segment[0] + segment[1] * segment[1];
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((point[0] - a[0]) * segment[0] + (point[1] - a[1]) * segment[1]) / length_squared)
+            .clamp(0.0, 1.0)
+    };
+    (point[0] - (a[0] + t * segment[0])).hypot(point[1] - (a[1] + t * segment[1]))
+}
+
+fn stroke_polyline(
+    image_buffer: &mut image::RgbaImage,
+    points: &[[f32; 2]],
+    width: f32,
+    color: [u8; 4],
+) {
+    let Some(first) = points.first() else {
+        return;
+    };
+    let reach = width / 2.0 + 1.0;
+    let (mut min, mut max) = (*first, *first);
+    for point in points {
+        (min, max) = (
+            [min[0].min(point[0]), min[1].min(point[1])],
+            [max[0].max(point[0]), max[1].max(point[1])],
+        );
+    }
+    let mut coverage = Coverage::new(
+        image_buffer,
+        [min[0] - reach, min[1] - reach],
+        [max[0] + reach, max[1] + reach],
+    );
+    if points.len() == 1 {
+        coverage.stroke_segment(*first, *first, width);
+    }
+    for segment in points.windows(2) {
+        coverage.stroke_segment(segment[0], segment[1], width);
+    }
+    coverage.blend_onto(image_buffer, color);
+}
+
+// source-over with the color's alpha scaled by `coverage`
+fn blend(image_buffer: &mut image::RgbaImage, x: u32, y: u32, color: [u8; 4], coverage: f32) {
+    let pixel = image_buffer.get_pixel_mut(x, y);
+    let alpha = f32::from(color[3]) / 255.0 * coverage;
+    let below_alpha = f32::from(pixel[3]) / 255.0;
+    let out_alpha = alpha + below_alpha * (1.0 - alpha);
+    if out_alpha <= 0.0 {
+        return;
+    }
+    for channel in 0..3 {
+        let value = (f32::from(color[channel]) * alpha
+            + f32::from(pixel[channel]) * below_alpha * (1.0 - alpha))
+            / out_alpha;
+        pixel[channel] = value.round() as u8;
+    }
+    pixel[3] = (out_alpha * 255.0).round() as u8;
+}
+
+// egui's proportional font, so the text in exports looks like the text in the editor
+fn font() -> ab_glyph::FontRef<'static> {
+    ab_glyph::FontRef::try_from_slice(epaint_default_fonts::UBUNTU_LIGHT)
+        .expect("egui's bundled font is a valid font")
+}
+
+// the glyphs of `text` positioned from a top left corner at the origin
+fn layout_text(font: &ab_glyph::FontRef, size: f32, text: &str) -> Vec<ab_glyph::Glyph> {
+    let scaled = ab_glyph::Font::as_scaled(font, size);
+    let ascent = ab_glyph::ScaleFont::ascent(&scaled);
+    let line_height = ab_glyph::ScaleFont::height(&scaled) + ab_glyph::ScaleFont::line_gap(&scaled);
+    let mut glyphs = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        let mut x = 0.0;
+        let mut previous = None;
+        for c in line.chars() {
+            let mut glyph = ab_glyph::ScaleFont::scaled_glyph(&scaled, c);
+            if let Some(previous) = previous {
+                x += ab_glyph::ScaleFont::kern(&scaled, previous, glyph.id);
+            }
+            glyph.position = ab_glyph::point(x, ascent + line_idx as f32 * line_height);
+            x += ab_glyph::ScaleFont::h_advance(&scaled, glyph.id);
+            previous = Some(glyph.id);
+            glyphs.push(glyph);
+        }
+    }
+    glyphs
+}
+
+fn text_size(font: &ab_glyph::FontRef, size: f32, text: &str) -> (f32, f32) {
+    let scaled = ab_glyph::Font::as_scaled(font, size);
+    let width = layout_text(font, size, text)
+        .iter()
+        .map(|glyph| glyph.position.x + ab_glyph::ScaleFont::h_advance(&scaled, glyph.id))
+        .fold(0.0, f32::max);
+    let lines = text.lines().count().max(1) as f32;
+    let height = lines * ab_glyph::ScaleFont::height(&scaled)
+        + (lines - 1.0) * ab_glyph::ScaleFont::line_gap(&scaled);
+    (width, height)
+}
+
+fn draw_text(
+    image_buffer: &mut image::RgbaImage,
+    font: &ab_glyph::FontRef,
+    at: [f32; 2],
+    size: f32,
+    text: &str,
+    color: [u8; 4],
+) {
+    for mut glyph in layout_text(font, size, text) {
+        glyph.position.x += at[0];
+        glyph.position.y += at[1];
+        let Some(outlined) = ab_glyph::Font::outline_glyph(font, glyph) else {
+            continue;
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|x, y, coverage| {
+            let x = bounds.min.x as i64 + i64::from(x);
+            let y = bounds.min.y as i64 + i64::from(y);
+            if (0..i64::from(image_buffer.width())).contains(&x)
+                && (0..i64::from(image_buffer.height())).contains(&y)
+            {
+                blend(image_buffer, x as u32, y as u32, color, coverage);
+            }
+        });
+    }
+}
@@ -0,0 +1,1268 @@
+#[derive(Debug, Clone)]
+pub struct WindowInfo {
+    pub id: u32,
+    pub pid: u32,
+    pub title: String,
+    pub app_name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MonitorInfo {
+    pub id: u32,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub is_primary: bool,
+}
+
+#[derive(Debug)]
+pub enum CaptureError {
+    WindowNotFound(String),
+    // the matcher description and every matching window, ordered by pid then id
+    AmbiguousWindow(String, Vec<WindowInfo>),
+    MonitorNotFound(String),
+    // the searched name and every monitor whose name contains it, ordered by id
+    AmbiguousMonitor(String, Vec<MonitorInfo>),
+    InvalidRegion(String),
+    Backend(String),
+}
+
+impl std::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureError::WindowNotFound(matcher) => write!(f, "Window with {} not found", matcher),
+            CaptureError::AmbiguousWindow(matcher, candidates) => {
+                write!(
+                    f,
+                    "{} windows match {}, narrow down the matcher:",
+                    candidates.len(),
+                    matcher
+                )?;
+                for window in candidates {
+                    write!(
+                        f,
+                        " [id {}, pid {}, app '{}', title '{}']",
+                        window.id, window.pid, window.app_name, window.title
+                    )?;
+                }
+                Ok(())
+            }
+            CaptureError::MonitorNotFound(name) => write!(f, "Monitor '{}' not found", name),
+            CaptureError::AmbiguousMonitor(name, candidates) => {
+                write!(
+                    f,
+                    "{} monitors match '{}', narrow down the name:",
+                    candidates.len(),
+                    name
+                )?;
+                for monitor in candidates {
+                    write!(f, " [id {}, name '{}']", monitor.id, monitor.name)?;
+                }
+                Ok(())
+            }
+            CaptureError::InvalidRegion(e) => write!(f, "Invalid capture region: {}", e),
+            CaptureError::Backend(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+impl From<xcap::XCapError> for CaptureError {
+    fn from(e: xcap::XCapError) -> Self {
+        CaptureError::Backend(e.to_string())
+    }
+}
+
+pub type CaptureResult<T> = Result<T, CaptureError>;
+
+pub trait CaptureSource: Send + Sync {
+    fn list_windows(&self) -> CaptureResult<Vec<WindowInfo>>;
+
+    fn capture_window(&self, window: &WindowInfo) -> CaptureResult<image::RgbaImage>;
+
+    fn list_monitors(&self) -> CaptureResult<Vec<MonitorInfo>>;
+
+    fn capture_monitor(&self, monitor: &MonitorInfo) -> CaptureResult<image::RgbaImage>;
+
+    // `WindowInfo::app_name` may be a full executable path depending on the platform
+    fn resolve_app_name(&self, window: &WindowInfo) -> CaptureResult<String> {
+        Ok(std::path::Path::new(&window.app_name)
+            .file_stem() // Gets filename without extension
+            .and_then(|name| name.to_str())
+            .unwrap_or("Unknown")
+            .to_string())
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct TitleRegex(pub regex::Regex);
+
+impl TryFrom<String> for TitleRegex {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        regex::Regex::new(&pattern).map(TitleRegex)
+    }
+}
+
+// every set field has to match, substring matches are case-insensitive
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WindowMatcher {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub title_exact: Option<String>,
+    #[serde(default)]
+    pub title_regex: Option<TitleRegex>,
+    #[serde(default)]
+    pub app_name: Option<String>,
+    // the executable name without extension, compared case-insensitively
+    #[serde(default)]
+    pub process_name: Option<String>,
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+impl WindowMatcher {
+    pub fn from_title(title: &str) -> Self {
+        Self {
+            title: Some(title.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.title.is_none()
+            && self.title_exact.is_none()
+            && self.title_regex.is_none()
+            && self.app_name.is_none()
+            && self.process_name.is_none()
+            && self.pid.is_none()
+        {
+            return Err(
+                "must set at least one of 'title', 'title_exact', 'title_regex', \
+                 'app_name', 'process_name' or 'pid'"
+                    .to_string(),
+            );
+        }
+        if [&self.title, &self.app_name, &self.process_name]
+            .into_iter()
+            .flatten()
+            .any(|pattern| pattern.trim().is_empty())
+        {
+            return Err("patterns must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, window: &WindowInfo, app_name: &str) -> bool {
+        let contains = |haystack: &str, needle: &Option<String>| {
+            needle
+                .as_ref()
+                .is_none_or(|needle| haystack.to_lowercase().contains(&needle.to_lowercase()))
+        };
+        contains(&window.title, &self.title)
+            && contains(app_name, &self.app_name)
+            && self
+                .title_exact
+                .as_ref()
+                .is_none_or(|title| window.title == *title)
+            && self
+                .title_regex
+                .as_ref()
+                .is_none_or(|TitleRegex(regex)| regex.is_match(&window.title))
+            && self
+                .process_name
+                .as_ref()
+                .is_none_or(|process_name| app_name.eq_ignore_ascii_case(process_name))
+            && self.pid.is_none_or(|pid| window.pid == pid)
+    }
+}
+
+impl std::fmt::Display for WindowMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut criteria = Vec::new();
+        if let Some(title) = &self.title {
+            criteria.push(format!("title containing '{}'", title));
+        }
+        if let Some(title) = &self.title_exact {
+            criteria.push(format!("title '{}'", title));
+        }
+        if let Some(TitleRegex(regex)) = &self.title_regex {
+            criteria.push(format!("title matching /{}/", regex));
+        }
+        if let Some(app_name) = &self.app_name {
+            criteria.push(format!("app containing '{}'", app_name));
+        }
+        if let Some(process_name) = &self.process_name {
+            criteria.push(format!("process '{}'", process_name));
+        }
+        if let Some(pid) = self.pid {
+            criteria.push(format!("pid {}", pid));
+        }
+        if criteria.is_empty() {
+            write!(f, "any window")
+        } else {
+            write!(f, "{}", criteria.join(", "))
+        }
+    }
+}
+
+// fails instead of guessing when more than one window matches
+pub fn find_window(
+    source: &dyn CaptureSource,
+    matcher: &WindowMatcher,
+) -> CaptureResult<WindowInfo> {
+    let mut candidates: Vec<_> = source
+        .list_windows()?
+        .into_iter()
+        .filter(|w| {
+            let app_name = source.resolve_app_name(w).unwrap_or_default();
+            matcher.matches(w, &app_name)
+        })
+        .collect();
+    candidates.sort_by_key(|w| (w.pid, w.id));
+
+    match candidates.len() {
+        0 => {
+            eprintln!("Window with {} not found", matcher);
+            Err(CaptureError::WindowNotFound(matcher.to_string()))
+        }
+        1 => Ok(candidates.remove(0)),
+        _ => Err(CaptureError::AmbiguousWindow(
+            matcher.to_string(),
+            candidates,
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionAnchor {
+    Window,
+    Monitor,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CaptureMode {
+    #[default]
+    Window,
+    // `monitor` is a case-insensitive substring of the monitor name, the primary one when unset
+    Monitor {
+        #[serde(default)]
+        monitor: Option<String>,
+    },
+    // every monitor stitched into one image following the desktop layout
+    AllMonitors,
+    // a fixed rectangle in pixels, relative to the top left of the window or monitor
+    Region {
+        anchor: RegionAnchor,
+        #[serde(default)]
+        monitor: Option<String>,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl CaptureMode {
+    pub fn needs_window(&self) -> bool {
+        matches!(
+            self,
+            CaptureMode::Window
+                | CaptureMode::Region {
+                    anchor: RegionAnchor::Window,
+                    ..
+                }
+        )
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let CaptureMode::Region {
+            x,
+            y,
+            width,
+            height,
+            ..
+        } = self
+        {
+            if *width == 0 || *height == 0 {
+                return Err("region 'width' and 'height' must be at least 1".to_string());
+            }
+            if x.checked_add(*width).is_none() || y.checked_add(*height).is_none() {
+                return Err("region extends past the largest possible image".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+// `None` picks the primary monitor, falling back to the first one
+pub fn find_monitor(source: &dyn CaptureSource, name: Option<&str>) -> CaptureResult<MonitorInfo> {
+    let monitors = source.list_monitors()?;
+    let Some(name) = name else {
+        return monitors
+            .iter()
+            .find(|m| m.is_primary)
+            .or(monitors.first())
+            .cloned()
+            .ok_or_else(|| CaptureError::MonitorNotFound("primary".to_string()));
+    };
+
+    let name_lower = name.to_lowercase();
+    let mut candidates: Vec<_> = monitors
+        .into_iter()
+        .filter(|m| m.name.to_lowercase().contains(&name_lower))
+        .collect();
+    candidates.sort_by_key(|m| m.id);
+    match candidates.len() {
+        0 => Err(CaptureError::MonitorNotFound(name.to_string())),
+        1 => Ok(candidates.remove(0)),
+        _ => Err(CaptureError::AmbiguousMonitor(name.to_string(), candidates)),
+    }
+}
+
+pub fn take_all_monitors_screenshot(source: &dyn CaptureSource) -> CaptureResult<image::RgbaImage> {
+    let monitors = source.list_monitors()?;
+    if monitors.is_empty() {
+        return Err(CaptureError::MonitorNotFound("any".to_string()));
+    }
+    let min_x = monitors.iter().map(|m| m.x).min().unwrap_or_default();
+    let min_y = monitors.iter().map(|m| m.y).min().unwrap_or_default();
+    let max_x = monitors
+        .iter()
+        .map(|m| m.x + m.width as i32)
+        .max()
+        .unwrap_or_default();
+    let max_y = monitors
+        .iter()
+        .map(|m| m.y + m.height as i32)
+        .max()
+        .unwrap_or_default();
+
+    let mut stitched = image::RgbaImage::new((max_x - min_x) as u32, (max_y - min_y) as u32);
+    for monitor in &monitors {
+        let monitor_image = source.capture_monitor(monitor)?;
+        image::imageops::overlay(
+            &mut stitched,
+            &monitor_image,
+            (monitor.x - min_x) as i64,
+            (monitor.y - min_y) as i64,
+        );
+    }
+    Ok(stitched)
+}
+
+fn crop_region(
+    image_buffer: &image::RgbaImage,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> CaptureResult<image::RgbaImage> {
+    let fits = |start: u32, length: u32, size: u32| {
+        start.checked_add(length).is_some_and(|end| end <= size)
+    };
+    if !fits(x, width, image_buffer.width()) || !fits(y, height, image_buffer.height()) {
+        return Err(CaptureError::InvalidRegion(format!(
+            "{}x{} at ({}, {}) exceeds the captured {}x{} image",
+            width,
+            height,
+            x,
+            y,
+            image_buffer.width(),
+            image_buffer.height()
+        )));
+    }
+    Ok(image::imageops::crop_imm(image_buffer, x, y, width, height).to_image())
+}
+
+fn profile_window(profile: &crate::config::CaptureProfile) -> CaptureResult<&WindowMatcher> {
+    profile.window.as_ref().ok_or_else(|| {
+        CaptureError::Backend(format!("Profile '{}' has no window matcher", profile.name))
+    })
+}
+
+// what the filename template knows about the capture target
+#[derive(Debug, Clone)]
+pub struct CaptureNames {
+    // the app name for window captures, the monitor otherwise
+    pub app: String,
+    pub title: String,
+    // the captured monitor, `None` for windows and the whole desktop
+    pub monitor: Option<String>,
+}
+
+// a capture after the profile's redaction rules
+pub struct CapturedFrame {
+    pub image: image::RgbaImage,
+    // the frame before redaction, only kept for profiles with `keep_unredacted`
+    pub unredacted: Option<image::RgbaImage>,
+    pub names: CaptureNames,
+}
+
+enum TargetKind {
+    Window(WindowInfo),
+    Monitor(MonitorInfo),
+    AllMonitors,
+}
+
+// the window or monitor a profile captures, looked up once so the frame and its names come from
+// the same one, both steps block on the backend
+struct CaptureTarget {
+    kind: TargetKind,
+    names: CaptureNames,
+}
+
+impl CaptureTarget {
+    fn resolve(
+        source: &dyn CaptureSource,
+        profile: &crate::config::CaptureProfile,
+    ) -> CaptureResult<Self> {
+        match &profile.mode {
+            CaptureMode::AllMonitors => Ok(Self {
+                kind: TargetKind::AllMonitors,
+                names: CaptureNames {
+                    app: "Desktop".to_string(),
+                    title: "Desktop".to_string(),
+                    monitor: None,
+                },
+            }),
+            CaptureMode::Monitor { monitor }
+            | CaptureMode::Region {
+                anchor: RegionAnchor::Monitor,
+                monitor,
+                ..
+            } => {
+                let monitor = find_monitor(source, monitor.as_deref())?;
+                Ok(Self {
+                    names: CaptureNames {
+                        app: monitor.name.clone(),
+                        title: monitor.name.clone(),
+                        monitor: Some(monitor.name.clone()),
+                    },
+                    kind: TargetKind::Monitor(monitor),
+                })
+            }
+            CaptureMode::Window
+            | CaptureMode::Region {
+                anchor: RegionAnchor::Window,
+                ..
+            } => {
+                let window = find_window(source, profile_window(profile)?)?;
+                // an app that cannot be looked up only costs its name
+                let app = source
+                    .resolve_app_name(&window)
+                    .unwrap_or_else(|_| "Unknown".to_string());
+                Ok(Self {
+                    names: CaptureNames {
+                        app,
+                        title: window.title.clone(),
+                        monitor: None,
+                    },
+                    kind: TargetKind::Window(window),
+                })
+            }
+        }
+    }
+
+    fn capture(
+        &self,
+        source: &dyn CaptureSource,
+        profile: &crate::config::CaptureProfile,
+    ) -> CaptureResult<CapturedFrame> {
+        let mut image = match &self.kind {
+            TargetKind::Window(window) => source.capture_window(window)?,
+            TargetKind::Monitor(monitor) => source.capture_monitor(monitor)?,
+            TargetKind::AllMonitors => take_all_monitors_screenshot(source)?,
+        };
+        if let CaptureMode::Region {
+            x,
+            y,
+            width,
+            height,
+            ..
+        } = profile.mode
+        {
+            image = crop_region(&image, x, y, width, height)?;
+        }
+        let unredacted =
+            (profile.keep_unredacted && !profile.redact.is_empty()).then(|| image.clone());
+        crate::redaction::apply(&mut image, &profile.redact);
+        Ok(CapturedFrame {
+            image,
+            unredacted,
+            names: self.names.clone(),
+        })
+    }
+}
+
+pub fn take_screenshot(
+    source: &dyn CaptureSource,
+    profile: &crate::config::CaptureProfile,
+) -> CaptureResult<CapturedFrame> {
+    CaptureTarget::resolve(source, profile)?.capture(source, profile)
+}
+
+pub fn capture_source_from_config(
+    config: &crate::config::RayshotConfig,
+) -> std::sync::Arc<dyn CaptureSource> {
+    match config.capture_backend {
+        crate::config::CaptureBackend::Xcap => std::sync::Arc::new(XcapCaptureSource),
+        // one fake window per targeted window, shaped to match its matcher, next to two monitors
+        crate::config::CaptureBackend::Fake => std::sync::Arc::new(
+            config
+                .profiles
+                .iter()
+                .filter_map(|profile| Some((profile, profile.window.as_ref()?)))
+                .fold(
+                    FakeCaptureSource::new()
+                        .with_monitor("Fake Monitor 1", 0, 0, 1920, 1080, true)
+                        .with_monitor("Fake Monitor 2", 1920, 0, 1280, 1024, false),
+                    |source, (profile, window)| {
+                        let title = window
+                            .title_exact
+                            .as_deref()
+                            .or(window.title.as_deref())
+                            .unwrap_or(&profile.name);
+                        // profiles sharing a window share the fake one too
+                        let exists = source
+                            .windows
+                            .lock()
+                            .unwrap()
+                            .iter()
+                            .any(|(w, _)| w.title == title);
+                        if exists {
+                            return source;
+                        }
+                        source.with_window(
+                            title,
+                            window.app_name.as_deref().unwrap_or("rayshot-fake"),
+                            1920,
+                            1080,
+                        )
+                    },
+                ),
+        ),
+    }
+}
+
+pub struct XcapCaptureSource;
+
+impl CaptureSource for XcapCaptureSource {
+    fn list_windows(&self) -> CaptureResult<Vec<WindowInfo>> {
+        let mut windows = Vec::new();
+        for window in xcap::Window::all()? {
+            // windows we can't query are not capturable anyway
+            let (Ok(id), Ok(title)) = (window.id(), window.title()) else {
+                continue;
+            };
+            windows.push(WindowInfo {
+                id,
+                pid: window.pid().unwrap_or_default(),
+                title,
+                app_name: window.app_name().unwrap_or_default(),
+            });
+        }
+        Ok(windows)
+    }
+
+    fn capture_window(&self, window: &WindowInfo) -> CaptureResult<image::RgbaImage> {
+        let target_window = xcap::Window::all()?
+            .into_iter()
+            .find(|w| w.id().is_ok_and(|id| id == window.id))
+            .ok_or_else(|| CaptureError::WindowNotFound(window.title.clone()))?;
+        Ok(target_window.capture_image()?)
+    }
+
+    fn list_monitors(&self) -> CaptureResult<Vec<MonitorInfo>> {
+        let mut monitors = Vec::new();
+        for monitor in xcap::Monitor::all()? {
+            monitors.push(MonitorInfo {
+                id: monitor.id()?,
+                name: monitor.name().unwrap_or_default(),
+                x: monitor.x()?,
+                y: monitor.y()?,
+                width: monitor.width()?,
+                height: monitor.height()?,
+                is_primary: monitor.is_primary().unwrap_or_default(),
+            });
+        }
+        Ok(monitors)
+    }
+
+    fn capture_monitor(&self, monitor: &MonitorInfo) -> CaptureResult<image::RgbaImage> {
+        let target_monitor = xcap::Monitor::all()?
+            .into_iter()
+            .find(|m| m.id().is_ok_and(|id| id == monitor.id))
+            .ok_or_else(|| CaptureError::MonitorNotFound(monitor.name.clone()))?;
+        Ok(target_monitor.capture_image()?)
+    }
+}
+
+pub enum FakeCaptureOutcome {
+    Image,
+    Fail(String),
+}
+
+// an in-memory capture source for headless runs, every capture renders a deterministic
+// gradient so consecutive frames differ, failures are scripted per call
+pub struct FakeCaptureSource {
+    windows: std::sync::Mutex<Vec<(WindowInfo, (u32, u32))>>,
+    monitors: std::sync::Mutex<Vec<MonitorInfo>>,
+    list_outcomes: std::sync::Mutex<std::collections::VecDeque<FakeCaptureOutcome>>,
+    capture_outcomes: std::sync::Mutex<std::collections::VecDeque<FakeCaptureOutcome>>,
+    capture_count: std::sync::atomic::AtomicU32,
+}
+
+impl FakeCaptureSource {
+    pub fn new() -> Self {
+        Self {
+            windows: std::sync::Mutex::new(Vec::new()),
+            monitors: std::sync::Mutex::new(Vec::new()),
+            list_outcomes: std::sync::Mutex::new(std::collections::VecDeque::new()),
+            capture_outcomes: std::sync::Mutex::new(std::collections::VecDeque::new()),
+            capture_count: std::sync::atomic::AtomicU32::new(0),
+        }
+    }
+
+    pub fn with_window(self, title: &str, app_name: &str, width: u32, height: u32) -> Self {
+        {
+            let mut windows = self.windows.lock().unwrap();
+            let id = windows.len() as u32 + 1;
+            windows.push((
+                WindowInfo {
+                    id,
+                    pid: 1000 + id,
+                    title: title.to_string(),
+                    app_name: app_name.to_string(),
+                },
+                (width, height),
+            ));
+        }
+        self
+    }
+
+    pub fn with_monitor(
+        self,
+        name: &str,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        is_primary: bool,
+    ) -> Self {
+        {
+            let mut monitors = self.monitors.lock().unwrap();
+            let id = monitors.len() as u32 + 1;
+            monitors.push(MonitorInfo {
+                id,
+                name: name.to_string(),
+                x,
+                y,
+                width,
+                height,
+                is_primary,
+            });
+        }
+        self
+    }
+
+    // queued outcomes are consumed one per call, an empty queue means success
+    pub fn script_list_windows(&self, outcome: FakeCaptureOutcome) {
+        self.list_outcomes.lock().unwrap().push_back(outcome);
+    }
+
+    pub fn script_capture(&self, outcome: FakeCaptureOutcome) {
+        self.capture_outcomes.lock().unwrap().push_back(outcome);
+    }
+
+    pub fn capture_count(&self) -> u32 {
+        self.capture_count.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    // window and monitor captures share the frame counter and the scripted outcomes
+    fn next_capture_frame(&self) -> CaptureResult<u32> {
+        let frame = self
+            .capture_count
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if let Some(FakeCaptureOutcome::Fail(e)) = self.capture_outcomes.lock().unwrap().pop_front()
+        {
+            return Err(CaptureError::Backend(e));
+        }
+        Ok(frame)
+    }
+
+    pub fn synthetic_image(width: u32, height: u32, frame: u32) -> image::RgbaImage {
+        image::RgbaImage::from_fn(width, height, |x, y| {
+            image::Rgba([
+                (x * 255 / width.max(1)) as u8,
+                (y * 255 / height.max(1)) as u8,
+                (frame % 256) as u8,
+                255,
+            ])
+        })
+    }
+}
+
+impl Default for FakeCaptureSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CaptureSource for FakeCaptureSource {
+    fn list_windows(&self) -> CaptureResult<Vec<WindowInfo>> {
+        if let Some(FakeCaptureOutcome::Fail(e)) = self.list_outcomes.lock().unwrap().pop_front() {
+            return Err(CaptureError::Backend(e));
+        }
+        Ok(self
+            .windows
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(window, _)| window.clone())
+            .collect())
+    }
+
+    fn capture_window(&self, window: &WindowInfo) -> CaptureResult<image::RgbaImage> {
+        let frame = self.next_capture_frame()?;
+        let windows = self.windows.lock().unwrap();
+        let (_, (width, height)) = windows
+            .iter()
+            .find(|(w, _)| w.id == window.id)
+            .ok_or_else(|| CaptureError::WindowNotFound(window.title.clone()))?;
+        Ok(Self::synthetic_image(*width, *height, frame))
+    }
+
+    fn list_monitors(&self) -> CaptureResult<Vec<MonitorInfo>> {
+        if let Some(FakeCaptureOutcome::Fail(e)) = self.list_outcomes.lock().unwrap().pop_front() {
+            return Err(CaptureError::Backend(e));
+        }
+        Ok(self.monitors.lock().unwrap().clone())
+    }
+
+    fn capture_monitor(&self, monitor: &MonitorInfo) -> CaptureResult<image::RgbaImage> {
+        let frame = self.next_capture_frame()?;
+        let monitors = self.monitors.lock().unwrap();
+        let monitor = monitors
+            .iter()
+            .find(|m| m.id == monitor.id)
+            .ok_or_else(|| CaptureError::MonitorNotFound(monitor.name.clone()))?;
+        Ok(Self::synthetic_image(monitor.width, monitor.height, frame))
+    }
+}
+
+// hands the frame to the UI overlay and waits for the user to drag the part to keep, the
+// unredacted frame is cut the same way
+async fn select_region(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    frame: CapturedFrame,
+) -> CaptureResult<Option<CapturedFrame>> {
+    let image_buffer = std::sync::Arc::new(frame.image);
+    let (reply, selection) = tokio::sync::oneshot::channel();
+    {
+        let mut region_selection = rayshot_state.region_selection.lock().await;
+        if region_selection.is_some() {
+            return Err(CaptureError::Backend(
+                "another region selection is still open".to_string(),
+            ));
+        }
+        *region_selection = Some(crate::state::RegionSelectionRequest {
+            image: image_buffer.clone(),
+            reply,
+        });
+    }
+    egui_ctx.request_repaint();
+
+    // a dropped reply means the overlay went away, treat it like Escape
+    let Ok(Some((x, y, width, height))) = selection.await else {
+        return Ok(None);
+    };
+    Ok(Some(CapturedFrame {
+        image: crop_region(&image_buffer, x, y, width, height)?,
+        unredacted: frame
+            .unredacted
+            .map(|unredacted| crop_region(&unredacted, x, y, width, height))
+            .transpose()?,
+        names: frame.names,
+    }))
+}
+
+// the capture → texture → save pipeline behind the capture hotkeys,
+// resolves once the screenshot is written to disk or has failed
+pub async fn capture_screenshot(
+    rayshot_state: crate::RayshotState,
+    egui_ctx: eframe::egui::Context,
+    profile_idx: usize,
+) {
+    let burst = rayshot_state
+        .config
+        .profiles
+        .get(profile_idx)
+        .and_then(|profile| profile.burst.clone());
+    match burst {
+        Some(burst) => capture_burst(rayshot_state, egui_ctx, profile_idx, burst).await,
+        None => capture_frame(rayshot_state, egui_ctx, profile_idx, None).await,
+    }
+}
+
+async fn is_capture_hotkey_held(rayshot_state: &crate::RayshotState, profile_idx: usize) -> bool {
+    let held_hotkeys = rayshot_state.held_hotkeys.lock().await;
+    held_hotkeys.contains(&crate::RayshotHotkey::CaptureProfile(profile_idx))
+        || held_hotkeys.contains(&crate::RayshotHotkey::CaptureScreenshot)
+}
+
+// every frame goes through the single capture, spawned on the burst interval
+async fn capture_burst(
+    rayshot_state: crate::RayshotState,
+    egui_ctx: eframe::egui::Context,
+    profile_idx: usize,
+    burst: crate::config::BurstConfig,
+) {
+    // key repeat while the hotkey is held must not start another burst
+    if !rayshot_state
+        .running_bursts
+        .lock()
+        .await
+        .insert(profile_idx)
+    {
+        return;
+    }
+    let burst_id = rayshot_state
+        .next_burst_id
+        .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(burst.interval_ms));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut frame_tasks = Vec::new();
+    for frame in 0..burst.count {
+        interval.tick().await;
+        if burst.while_held
+            && frame > 0
+            && !is_capture_hotkey_held(&rayshot_state, profile_idx).await
+        {
+            break;
+        }
+        frame_tasks.push(tokio::task::spawn(capture_frame(
+            rayshot_state.clone(),
+            egui_ctx.clone(),
+            profile_idx,
+            Some(burst_id),
+        )));
+    }
+    rayshot_state
+        .running_bursts
+        .lock()
+        .await
+        .remove(&profile_idx);
+
+    for frame_task in frame_tasks {
+        if let Err(e) = frame_task.await {
+            eprintln!("Burst frame task failed: {}", e);
+        }
+    }
+}
+
+// the entry signals the UI that a new screenshot is on its way
+async fn push_capture_entry(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    filename: String,
+    burst_id: Option<u64>,
+) -> crate::ScreenshotEntry {
+    let mut screenshot_entry =
+        crate::ScreenshotEntry::new(std::sync::Arc::new(filename), crate::FileLocation::Local);
+    screenshot_entry.burst_id = burst_id;
+    screenshot_entry.state.lock().await.capturing = true;
+    rayshot_state
+        .screenshot_entries
+        .lock()
+        .await
+        .push(screenshot_entry.clone());
+    rayshot_state.manage_texture_cache(egui_ctx).await;
+    egui_ctx.request_repaint();
+    screenshot_entry
+}
+
+async fn capture_frame(
+    rayshot_state: crate::RayshotState,
+    egui_ctx: eframe::egui::Context,
+    profile_idx: usize,
+    burst_id: Option<u64>,
+) {
+    let config = rayshot_state.config.clone();
+    let Some(profile) = config.profiles.get(profile_idx) else {
+        return crate::state::report_error(
+            &rayshot_state,
+            &egui_ctx,
+            format!("Unknown capture profile #{}", profile_idx),
+        )
+        .await;
+    };
+
+    // look up the window or monitor first, so the entry can be named while the frame is taken
+    let capture_source = rayshot_state.capture_source.clone();
+    let task_profile = profile.clone();
+    let target = match tokio::task::spawn_blocking(move || {
+        CaptureTarget::resolve(capture_source.as_ref(), &task_profile)
+    })
+    .await
+    {
+        Ok(Ok(target)) => target,
+        Ok(Err(error)) => {
+            return crate::state::report_error(
+                &rayshot_state,
+                &egui_ctx,
+                format!(
+                    "Failed to capture screenshot for profile '{}': {}",
+                    profile.name, error
+                ),
+            )
+            .await;
+        }
+        Err(error) => {
+            return crate::state::report_error(
+                &rayshot_state,
+                &egui_ctx,
+                format!("Task failed for profile '{}': {}", profile.name, error),
+            )
+            .await;
+        }
+    };
+    let capture_names = target.names.clone();
+    let capture_source = rayshot_state.capture_source.clone();
+    let task_profile = profile.clone();
+    let screenshot_task =
+        tokio::task::spawn_blocking(move || target.capture(capture_source.as_ref(), &task_profile));
+
+    let claim_filename = |image_buffer: Option<&image::RgbaImage>| {
+        crate::storage::claim_profile_filename(&config, profile, &capture_names, image_buffer)
+            .map_err(|e| {
+                format!(
+                    "Failed to name screenshot for profile '{}': {}",
+                    profile.name, e
+                )
+            })
+    };
+
+    // name the file and show the entry right away, unless the name depends on the frame
+    // or the capture can still be cancelled in the overlay
+    let mut early_entry = None;
+    let name_needs_image = config.filename_template.needs_image()
+        || config
+            .directory_template
+            .as_ref()
+            .is_some_and(|template| template.needs_image());
+    if !profile.select_region && !name_needs_image {
+        match claim_filename(None) {
+            Ok(filename) => {
+                early_entry =
+                    Some(push_capture_entry(&rayshot_state, &egui_ctx, filename, burst_id).await);
+            }
+            Err(error_msg) => {
+                return crate::state::report_error(&rayshot_state, &egui_ctx, error_msg).await;
+            }
+        }
+    }
+
+    let handle_error = |error_msg: String| async {
+        if let Some(screenshot_entry) = &early_entry {
+            screenshot_entry.state.lock().await.failed = true;
+            // drop the placeholder claimed for the name
+            let _ = std::fs::remove_file(
+                config
+                    .screenshot_dir
+                    .join(screenshot_entry.filename.as_str()),
+            );
+        }
+        crate::state::report_error(&rayshot_state, &egui_ctx, error_msg).await;
+    };
+
+    // receive the screenshot
+    let mut frame = match screenshot_task.await {
+        Ok(Ok(frame)) => frame,
+        Ok(Err(error)) => {
+            handle_error(format!(
+                "Failed to capture screenshot for profile '{}': {}",
+                profile.name, error
+            ))
+            .await;
+            return;
+        }
+        Err(error) => {
+            handle_error(format!(
+                "Task failed for profile '{}': {}",
+                profile.name, error
+            ))
+            .await;
+            return;
+        }
+    };
+    if profile.select_region {
+        match select_region(&rayshot_state, &egui_ctx, frame).await {
+            Ok(Some(region)) => frame = region,
+            Ok(None) => return,
+            Err(error) => {
+                handle_error(format!(
+                    "Failed to select a region for profile '{}': {}",
+                    profile.name, error
+                ))
+                .await;
+                return;
+            }
+        }
+    }
+    let CapturedFrame {
+        image: image_buffer,
+        unredacted,
+        ..
+    } = frame;
+    let screenshot_entry = match early_entry.clone() {
+        Some(early_entry) => early_entry,
+        None => match claim_filename(Some(&image_buffer)) {
+            Ok(filename) => push_capture_entry(&rayshot_state, &egui_ctx, filename, burst_id).await,
+            Err(error_msg) => return handle_error(error_msg).await,
+        },
+    };
+    *screenshot_entry.demension.lock().await = Some((
+        image_buffer.width() as usize,
+        image_buffer.height() as usize,
+    ));
+
+    // write the screenshot to gpu for UI display, then to file
+    let encoder = profile.encoder;
+    let screenshot_file_path = config
+        .screenshot_dir
+        .join(screenshot_entry.filename.as_str());
+    // monitor captures have no app or window
+    let is_window_capture = profile.mode.needs_window();
+    let record = crate::metadata::ScreenshotMetadata {
+        captured_at: Some(chrono::Local::now().naive_local()),
+        app_name: is_window_capture.then_some(capture_names.app),
+        window_title: is_window_capture.then_some(capture_names.title),
+        monitor: capture_names.monitor,
+        ..Default::default()
+    };
+    if let Some(unredacted) = unredacted {
+        tokio::task::spawn(save_unredacted(
+            rayshot_state.clone(),
+            egui_ctx.clone(),
+            screenshot_entry.clone(),
+            unredacted,
+            encoder,
+            record.clone(),
+        ));
+    }
+    let write_task = tokio::task::spawn_blocking(move || {
+        let texture_handle = egui_ctx.load_texture(
+            screenshot_entry.filename.as_str(),
+            eframe::epaint::ColorImage::from_rgba_unmultiplied(
+                [
+                    image_buffer.width() as usize,
+                    image_buffer.height() as usize,
+                ],
+                image_buffer.as_raw(),
+            ),
+            Default::default(),
+        );
+        screenshot_entry
+            .texture_handle
+            .blocking_lock()
+            .replace(texture_handle);
+        // entries pushed or dropped since the capture started shift its position
+        {
+            let mut idx = rayshot_state.cur_screenshot_idx.blocking_lock();
+            let entries = rayshot_state.screenshot_entries.blocking_lock();
+            if let Some(position) = entries
+                .iter()
+                .position(|entry| entry.filename == screenshot_entry.filename)
+            {
+                *idx = position;
+            }
+        }
+        {
+            let mut screenshot_state = screenshot_entry.state.blocking_lock();
+            screenshot_state.capturing = false;
+            screenshot_state.writing = true;
+        }
+        {
+            let _file_lock = screenshot_entry.file_lock.blocking_lock();
+            egui_ctx.request_repaint();
+            let saved = crate::storage::save_image(&image_buffer, &screenshot_file_path, encoder)
+                .map_err(|e| {
+                    let err_str = format!("Failed to save screenshot: {}", e);
+                    eprintln!("{}", err_str);
+                    rayshot_state.error_messages.blocking_lock().push(err_str);
+                    screenshot_entry.state.blocking_lock().failed = true;
+                });
+            let file_size = std::fs::metadata(&screenshot_file_path)
+                .ok()
+                .map(|metadata| metadata.len());
+            if let Some(file_size) = file_size {
+                screenshot_entry
+                    .file_size
+                    .blocking_lock()
+                    .replace(file_size as usize);
+            }
+            if saved.is_ok() {
+                crate::metadata::update_blocking(&rayshot_state, |metadata| {
+                    let metadata = metadata.entry(screenshot_entry.filename.as_str());
+                    *metadata = crate::metadata::ScreenshotMetadata {
+                        file_size,
+                        burst_id,
+                        ..record
+                    };
+                    metadata.set_image(&image_buffer);
+                });
+                if let Some(content) = rayshot_state.config.copy_after_capture {
+                    if let Err(e) = crate::clipboard::copy_blocking(
+                        &rayshot_state.clipboard,
+                        content,
+                        &screenshot_file_path,
+                        Some(&image_buffer),
+                    ) {
+                        let err_str = format!(
+                            "Failed to copy '{}' to the clipboard: {}",
+                            screenshot_entry.filename, e
+                        );
+                        eprintln!("{}", err_str);
+                        rayshot_state.error_messages.blocking_lock().push(err_str);
+                    }
+                }
+            }
+        }
+        screenshot_entry.state.blocking_lock().writing = false;
+        egui_ctx.request_repaint();
+    });
+    if let Err(e) = write_task.await {
+        eprintln!("Screenshot write task failed: {}", e);
+    }
+}
+
+// saves the frame from before redaction next to the capture and shows it right after it
+async fn save_unredacted(
+    rayshot_state: crate::RayshotState,
+    egui_ctx: eframe::egui::Context,
+    capture_entry: crate::ScreenshotEntry,
+    image_buffer: image::RgbaImage,
+    encoder: crate::storage::EncoderConfig,
+    mut record: crate::metadata::ScreenshotMetadata,
+) {
+    let config = rayshot_state.config.clone();
+    let filename = capture_entry.filename.clone();
+    let saved = tokio::task::spawn_blocking(move || {
+        let trash_dir = (config.trash_backend == crate::config::TrashBackend::Folder)
+            .then_some(config.trash_dir.as_path());
+        let original = crate::storage::save_unredacted(
+            &config.screenshot_dir,
+            trash_dir,
+            &filename,
+            &image_buffer,
+            encoder,
+        )?;
+        record.file_size = std::fs::metadata(config.screenshot_dir.join(&original))
+            .ok()
+            .map(|metadata| metadata.len());
+        record.set_image(&image_buffer);
+        Ok::<_, std::io::Error>((original, record))
+    })
+    .await
+    .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+
+    match saved {
+        Ok((original, record)) => {
+            let original_entry = crate::ScreenshotEntry::new(
+                std::sync::Arc::new(original.clone()),
+                crate::FileLocation::Local,
+            );
+            *original_entry.file_size.lock().await = record.file_size.map(|size| size as usize);
+            rayshot_state
+                .insert_entry_after(&capture_entry, original_entry)
+                .await;
+            crate::metadata::update(&rayshot_state, &egui_ctx, |metadata| {
+                *metadata.entry(&original) = record;
+            })
+            .await;
+            rayshot_state.manage_texture_cache(&egui_ctx).await;
+            egui_ctx.request_repaint();
+        }
+        Err(e) => {
+            crate::state::report_error(
+                &rayshot_state,
+                &egui_ctx,
+                format!(
+                    "Failed to save the unredacted original of '{}': {}",
+                    capture_entry.filename, e
+                ),
+            )
+            .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn crop_region_keeps_regions_inside_the_image() {
+        let image_buffer =
+            image::RgbaImage::from_fn(8, 6, |x, y| image::Rgba([x as u8, y as u8, 0, 255]));
+        let cropped = super::crop_region(&image_buffer, 2, 1, 6, 5).unwrap();
+        assert_eq!(cropped.dimensions(), (6, 5));
+        assert_eq!(cropped.get_pixel(0, 0).0, [2, 1, 0, 255]);
+        assert_eq!(cropped.get_pixel(5, 4).0, [7, 5, 0, 255]);
+
+        for (x, y, width, height) in [
+            (3, 0, 6, 6),
+            (0, 1, 8, 6),
+            (u32::MAX, 0, 2, 1),
+            (0, 1, 1, u32::MAX),
+        ] {
+            assert!(
+                matches!(
+                    super::crop_region(&image_buffer, x, y, width, height),
+                    Err(super::CaptureError::InvalidRegion(_))
+                ),
+                "{}x{} at ({}, {})",
+                width,
+                height,
+                x,
+                y
+            );
+        }
+    }
+
+    #[test]
+    fn find_monitor_reports_every_monitor_an_ambiguous_name_matches() {
+        let source = super::FakeCaptureSource::new()
+            .with_monitor("DP-1", 0, 0, 1920, 1080, false)
+            .with_monitor("DP-2", 1920, 0, 1920, 1080, true)
+            .with_monitor("HDMI-1", 3840, 0, 1280, 1024, false);
+        let find = |name| super::find_monitor(&source, name);
+        assert_eq!(find(None).unwrap().name, "DP-2");
+        assert_eq!(find(Some("hdmi")).unwrap().name, "HDMI-1");
+        assert!(matches!(
+            find(Some("VGA")),
+            Err(super::CaptureError::MonitorNotFound(_))
+        ));
+        match find(Some("dp")) {
+            Err(super::CaptureError::AmbiguousMonitor(name, candidates)) => {
+                assert_eq!(name, "dp");
+                let names: Vec<_> = candidates.iter().map(|m| m.name.as_str()).collect();
+                assert_eq!(names, ["DP-1", "DP-2"]);
+            }
+            other => panic!(
+                "expected an ambiguous monitor, got {:?}",
+                other.map(|m| m.name)
+            ),
+        }
+    }
+}
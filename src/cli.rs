@@ -0,0 +1,614 @@
+pub const USAGE: &str = "Usage:
+  rayshot                          launch the GUI
+  rayshot capture [--profile <name>] [--window <substring>] [--monitor <substring>]
+                  [--all-monitors] [--out <dir>] [--format png|jpg|webp|avif]
+  rayshot list-windows
+  rayshot list-monitors
+  rayshot trash <file>
+  rayshot restore <file>
+  rayshot purge-trash
+  rayshot edit <file> [--crop <x>,<y>,<width>,<height>] [--aspect <width>:<height>]
+               [--rotate 90|180|270] [--flip horizontal|vertical] [--resize <width>x<height>]
+               [--filter nearest|triangle|catmullrom|gaussian|lanczos3]
+                                   edits apply in the order given, undo in the GUI reverts them
+  rayshot help";
+
+pub enum Command {
+    Capture {
+        profile: Option<String>,
+        window: Option<String>,
+        mode: Option<rayshot::capture::CaptureMode>,
+        out: Option<std::path::PathBuf>,
+        // overrides the profile's encoder, with that encoder's default settings
+        format: Option<rayshot::storage::OutputFormat>,
+    },
+    ListWindows,
+    ListMonitors,
+    Trash(String),
+    Restore(String),
+    PurgeTrash,
+    Edit {
+        file: String,
+        edits: Vec<rayshot::edit::Edit>,
+    },
+    Help,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let Some((subcommand, rest)) = args.split_first() else {
+        return Err("Missing command".to_string());
+    };
+    match subcommand.as_str() {
+        "capture" => {
+            let mut profile = None;
+            let mut window = None;
+            let mut mode = None;
+            let mut out = None;
+            let mut format = None;
+            let mut rest = rest.iter();
+            while let Some(flag) = rest.next() {
+                if flag == "--all-monitors" {
+                    mode = Some(rayshot::capture::CaptureMode::AllMonitors);
+                    continue;
+                }
+                let Some(value) = rest.next() else {
+                    return Err(format!("Missing value for '{}'", flag));
+                };
+                match flag.as_str() {
+                    "--profile" => profile = Some(value.clone()),
+                    "--window" => {
+                        window = Some(value.clone());
+                        mode = Some(rayshot::capture::CaptureMode::Window);
+                    }
+                    "--monitor" => {
+                        mode = Some(rayshot::capture::CaptureMode::Monitor {
+                            monitor: Some(value.clone()),
+                        })
+                    }
+                    "--out" => out = Some(std::path::PathBuf::from(value)),
+                    "--format" => format = Some(value.parse()?),
+                    _ => return Err(format!("Unknown option '{}'", flag)),
+                }
+            }
+            Ok(Command::Capture {
+                profile,
+                window,
+                mode,
+                out,
+                format,
+            })
+        }
+        "list-windows" if rest.is_empty() => Ok(Command::ListWindows),
+        "list-monitors" if rest.is_empty() => Ok(Command::ListMonitors),
+        "purge-trash" if rest.is_empty() => Ok(Command::PurgeTrash),
+        "trash" | "restore" => {
+            let [file] = rest else {
+                return Err(format!("'{}' takes exactly one file", subcommand));
+            };
+            if subcommand == "trash" {
+                Ok(Command::Trash(file.clone()))
+            } else {
+                Ok(Command::Restore(file.clone()))
+            }
+        }
+        "edit" => {
+            let Some((file, rest)) = rest.split_first() else {
+                return Err("'edit' takes a file".to_string());
+            };
+            let mut edits = Vec::new();
+            let mut filter = rayshot::edit::ResizeFilter::default();
+            let mut rest = rest.iter();
+            while let Some(flag) = rest.next() {
+                let Some(value) = rest.next() else {
+                    return Err(format!("Missing value for '{}'", flag));
+                };
+                edits.push(match flag.as_str() {
+                    "--crop" => {
+                        let [x, y, width, height] = parse_numbers(flag, value, ',')?;
+                        rayshot::edit::Edit::Crop {
+                            x,
+                            y,
+                            width,
+                            height,
+                        }
+                    }
+                    "--aspect" => {
+                        let [width, height] = parse_numbers(flag, value, ':')?;
+                        rayshot::edit::Edit::AspectRatio { width, height }
+                    }
+                    "--rotate" => rayshot::edit::Edit::Rotate(value.parse()?),
+                    "--flip" => match value.as_str() {
+                        "horizontal" => rayshot::edit::Edit::FlipHorizontal,
+                        "vertical" => rayshot::edit::Edit::FlipVertical,
+                        _ => return Err(format!("Unknown flip '{}'", value)),
+                    },
+                    "--resize" => {
+                        let [width, height] = parse_numbers(flag, value, 'x')?;
+                        rayshot::edit::Edit::Resize {
+                            width,
+                            height,
+                            filter,
+                        }
+                    }
+                    "--filter" => {
+                        filter = value.parse()?;
+                        continue;
+                    }
+                    _ => return Err(format!("Unknown option '{}'", flag)),
+                });
+            }
+            // one filter for every resize, wherever it is given
+            for edit in &mut edits {
+                if let rayshot::edit::Edit::Resize {
+                    filter: resize_filter,
+                    ..
+                } = edit
+                {
+                    *resize_filter = filter;
+                }
+            }
+            if edits.is_empty() {
+                return Err("'edit' needs at least one edit".to_string());
+            }
+            Ok(Command::Edit {
+                file: file.clone(),
+                edits,
+            })
+        }
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        _ => Err(format!("Unknown command '{}'", args.join(" "))),
+    }
+}
+
+// `N` numbers separated by `separator`, like `1920x1080`
+fn parse_numbers<const N: usize>(
+    flag: &str,
+    value: &str,
+    separator: char,
+) -> Result<[u32; N], String> {
+    let invalid = || format!("Invalid value '{}' for '{}'", value, flag);
+    let numbers: Vec<u32> = value
+        .split(separator)
+        .map(|number| number.trim().parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    numbers.try_into().map_err(|_| invalid())
+}
+
+pub fn run(command: Command, config: rayshot::config::RayshotConfig) -> Result<(), String> {
+    match command {
+        Command::Capture {
+            profile,
+            window,
+            mode,
+            out,
+            format,
+        } => {
+            let mut profile = match profile {
+                Some(name) => config
+                    .profile_by_name(&name)
+                    .map(|(_, profile)| profile.clone())
+                    .ok_or_else(|| format!("Unknown profile '{}'", name))?,
+                None => config.profiles[0].clone(),
+            };
+            if let Some(window) = window {
+                profile.window = Some(rayshot::capture::WindowMatcher::from_title(&window));
+            }
+            // the last of `--window`, `--monitor` and `--all-monitors` wins over the profile mode
+            if let Some(mode) = mode {
+                profile.mode = mode;
+            }
+            if profile.window.is_none() && profile.mode.needs_window() {
+                return Err(format!(
+                    "Profile '{}' has no window to capture, pass '--window'",
+                    profile.name
+                ));
+            }
+            // names in the screenshot dir are kept free in the trash folder too, like GUI captures
+            let uses_trash_dir = config.trash_backend == rayshot::config::TrashBackend::Folder;
+            let in_screenshot_dir = out.is_none();
+            let trash_dir = (in_screenshot_dir && uses_trash_dir).then(|| {
+                config
+                    .trash_dir
+                    .join(profile.output_subdir.clone().unwrap_or_default())
+            });
+            let output_dir = out.unwrap_or_else(|| profile.output_dir(&config));
+            std::fs::create_dir_all(&output_dir).map_err(|e| {
+                format!(
+                    "Failed to create directory '{}': {}",
+                    output_dir.display(),
+                    e
+                )
+            })?;
+
+            let capture_source = rayshot::capture::capture_source_from_config(&config);
+            let rayshot::capture::CapturedFrame {
+                image: image_buffer,
+                unredacted,
+                names: capture_names,
+            } = rayshot::capture::take_screenshot(capture_source.as_ref(), &profile).map_err(
+                |e| {
+                    format!(
+                        "Failed to capture screenshot for profile '{}': {}",
+                        profile.name, e
+                    )
+                },
+            )?;
+
+            let encoder = match format {
+                Some(format) if format != profile.encoder.format() => format.into(),
+                _ => profile.encoder,
+            };
+            let captured_at = chrono::Local::now().naive_local();
+            let filename = rayshot::storage::claim_filename(
+                &config,
+                &profile,
+                &capture_names,
+                Some(&image_buffer),
+                encoder.format(),
+                &output_dir,
+                trash_dir.as_deref(),
+            )
+            .map_err(|e| format!("Failed to name screenshot: {}", e))?;
+            let screenshot_file_path = output_dir.join(&filename);
+            rayshot::storage::save_image(&image_buffer, &screenshot_file_path, encoder)
+                .map_err(|e| format!("Failed to save screenshot: {}", e))?;
+            let original = unredacted
+                .map(|unredacted| {
+                    rayshot::storage::save_unredacted(
+                        &output_dir,
+                        trash_dir.as_deref(),
+                        &filename,
+                        &unredacted,
+                        encoder,
+                    )
+                    .map(|original| (original, unredacted))
+                    .map_err(|e| format!("Failed to save the unredacted original: {}", e))
+                })
+                .transpose()?;
+            if in_screenshot_dir {
+                let subdir = profile.output_subdir.clone().unwrap_or_default();
+                let is_window_capture = profile.mode.needs_window();
+                let record = rayshot::metadata::ScreenshotMetadata {
+                    captured_at: Some(captured_at),
+                    app_name: is_window_capture.then_some(capture_names.app),
+                    window_title: is_window_capture.then_some(capture_names.title),
+                    monitor: capture_names.monitor,
+                    ..Default::default()
+                };
+                update_metadata(&config, |metadata| {
+                    let written = std::iter::once((&filename, &image_buffer)).chain(
+                        original
+                            .as_ref()
+                            .map(|(original, unredacted)| (original, unredacted)),
+                    );
+                    for (name, image_buffer) in written {
+                        let file_size = std::fs::metadata(output_dir.join(name))
+                            .ok()
+                            .map(|metadata| metadata.len());
+                        let metadata = metadata.entry(&subdir.join(name).to_string_lossy());
+                        *metadata = rayshot::metadata::ScreenshotMetadata {
+                            file_size,
+                            ..record.clone()
+                        };
+                        metadata.set_image(image_buffer);
+                    }
+                })?;
+            }
+            println!("{}", screenshot_file_path.display());
+            if let Some((original, _)) = &original {
+                println!("{}", output_dir.join(original).display());
+            }
+        }
+        Command::ListWindows => {
+            let capture_source = rayshot::capture::capture_source_from_config(&config);
+            let windows = capture_source
+                .list_windows()
+                .map_err(|e| format!("Failed to list windows: {}", e))?;
+            for window in windows {
+                let app_name = capture_source
+                    .resolve_app_name(&window)
+                    .unwrap_or_else(|_| "Unknown".to_string());
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    window.id, window.pid, app_name, window.title
+                );
+            }
+        }
+        Command::ListMonitors => {
+            let capture_source = rayshot::capture::capture_source_from_config(&config);
+            let monitors = capture_source
+                .list_monitors()
+                .map_err(|e| format!("Failed to list monitors: {}", e))?;
+            for monitor in monitors {
+                println!(
+                    "{}\t{}x{}+{}+{}{}\t{}",
+                    monitor.id,
+                    monitor.width,
+                    monitor.height,
+                    monitor.x,
+                    monitor.y,
+                    if monitor.is_primary { " primary" } else { "" },
+                    monitor.name
+                );
+            }
+        }
+        Command::Trash(filename) => {
+            move_file(&config, &filename, rayshot::FileLocation::Local)?;
+        }
+        Command::Restore(filename) => {
+            move_file(&config, &filename, rayshot::FileLocation::Trash)?;
+        }
+        Command::PurgeTrash => {
+            if !config.trash_retention.is_enabled() {
+                return Err("No trash_retention limit is configured".to_string());
+            }
+            let trash_times = rayshot::metadata::MetadataIndex::load(&config)
+                .map_err(|e| e.to_string())?
+                .trash_times();
+            let rayshot::storage::TrashPurge { purged, errors } =
+                rayshot::storage::purge_trash(&config, &config.trash_retention, &trash_times)
+                    .map_err(|e| format!("Failed to purge the trash: {}", e))?;
+            // the desktop trash is listed by where the files were trashed from
+            let dir = match config.trash_backend {
+                rayshot::config::TrashBackend::Folder => &config.trash_dir,
+                rayshot::config::TrashBackend::Freedesktop => &config.screenshot_dir,
+            };
+            update_metadata(&config, |metadata| {
+                for filename in &purged {
+                    metadata.remove(filename);
+                }
+            })?;
+            for filename in purged {
+                println!("{}", dir.join(filename).display());
+            }
+            // what was deleted is listed and forgotten before the failures are reported
+            if !errors.is_empty() {
+                return Err(errors.join("\n"));
+            }
+        }
+        Command::Edit { file, edits } => {
+            let filename = resolve_file(&config, &file, rayshot::FileLocation::Local)?;
+            let path = config.screenshot_dir.join(&filename);
+            rayshot::journal::Journal::load(&config).map_err(|e| e.to_string())?;
+            let (backup, (width, height), edited) =
+                rayshot::storage::edit_screenshot(&config, &path, &filename, &edits)
+                    .map_err(|e| format!("Failed to edit '{}': {}", filename, e))?;
+            // annotations move along with the pixels, like in the GUI
+            let mut annotations = (Vec::new(), Vec::new());
+            update_metadata(&config, |metadata| {
+                if let Some(metadata) = metadata.get_mut(&filename) {
+                    let after = rayshot::edit::transform_annotations(
+                        &metadata.annotations,
+                        &edits,
+                        width,
+                        height,
+                    )
+                    .unwrap_or_default();
+                    let before = std::mem::replace(&mut metadata.annotations, after.clone());
+                    annotations = (before, after);
+                    metadata.set_image(&edited);
+                    metadata.file_size = std::fs::metadata(&path).ok().map(|file| file.len());
+                }
+            })?;
+            let (before, after) = annotations;
+            record_step(
+                &config,
+                rayshot::journal::FileOperation::Edit {
+                    filename,
+                    backup,
+                    before,
+                    after,
+                },
+            )?;
+            println!("{}", path.display());
+        }
+        Command::Help => println!("{}", USAGE),
+    }
+    Ok(())
+}
+
+// the path of the screenshot relative to its location, which it must be in
+fn resolve_file(
+    config: &rayshot::config::RayshotConfig,
+    file: &str,
+    current_location: rayshot::FileLocation,
+) -> Result<String, String> {
+    // files in the desktop trash are named by their path in the screenshot directory
+    let current_dir = match (current_location, config.trash_backend) {
+        (rayshot::FileLocation::Trash, rayshot::config::TrashBackend::Freedesktop) => {
+            &config.screenshot_dir
+        }
+        _ => current_location.dir_path(config),
+    };
+    // accept both paths relative to the screenshot or trash directory and paths into it
+    let file_path = std::path::Path::new(file);
+    let relative_path = file_path.strip_prefix(current_dir).unwrap_or(file_path);
+    if !relative_path
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)))
+    {
+        return Err(format!(
+            "Invalid file '{}', expected a path inside '{}'",
+            file,
+            current_dir.display()
+        ));
+    }
+    let filename = relative_path.to_string_lossy().into_owned();
+    if !current_location
+        .file_path(config, None, &filename)
+        .is_ok_and(|path| path.is_file())
+    {
+        return Err(match current_location {
+            rayshot::FileLocation::Trash => {
+                format!("Screenshot '{}' not found in the trash", filename)
+            }
+            rayshot::FileLocation::Local => format!(
+                "Screenshot '{}' not found in '{}'",
+                filename,
+                current_dir.display()
+            ),
+        });
+    }
+    Ok(filename)
+}
+
+fn move_file(
+    config: &rayshot::config::RayshotConfig,
+    file: &str,
+    current_location: rayshot::FileLocation,
+) -> Result<(), String> {
+    let filename = resolve_file(config, file, current_location)?;
+    let filename = filename.as_str();
+    rayshot::storage::ensure_directories(config).map_err(|e| e.to_string())?;
+    // recorded so the GUI can undo it later, a broken journal fails before anything moves
+    rayshot::journal::Journal::load(config).map_err(|e| e.to_string())?;
+    let target_location =
+        rayshot::storage::move_screenshot(config, None, filename, current_location)
+            .map_err(|e| e.to_string())?;
+    record_step(
+        config,
+        rayshot::journal::FileOperation::Move {
+            filename: filename.to_string(),
+            from: current_location,
+        },
+    )?;
+    update_metadata(config, |metadata| {
+        if let Some(metadata) = metadata.get_mut(filename) {
+            metadata.set_location(target_location);
+        }
+    })?;
+    let target_path = target_location
+        .file_path(config, None, filename)
+        .map_err(|e| e.to_string())?;
+    println!("{}", target_path.display());
+    Ok(())
+}
+
+// adds the operation to the journal the GUI undoes from
+fn record_step(
+    config: &rayshot::config::RayshotConfig,
+    operation: rayshot::journal::FileOperation,
+) -> Result<(), String> {
+    let (_, forgotten) = rayshot::journal::Journal::update(config, |journal| {
+        journal.record(rayshot::journal::JournalStep {
+            operations: vec![operation],
+        })
+    })
+    .map_err(|e| format!("Failed to save the undo journal: {}", e))?;
+    rayshot::journal::remove_backups(config, &forgotten);
+    Ok(())
+}
+
+// keeps the GUI's metadata index in sync, a running GUI picks the change up from the file
+fn update_metadata(
+    config: &rayshot::config::RayshotConfig,
+    update: impl FnOnce(&mut rayshot::metadata::MetadataIndex),
+) -> Result<(), String> {
+    rayshot::metadata::MetadataIndex::update_file(config, update)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to save the metadata index: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    fn parse(args: &str) -> Result<super::Command, String> {
+        let args: Vec<_> = args.split_whitespace().map(str::to_string).collect();
+        super::parse_args(&args)
+    }
+
+    #[test]
+    fn capture_options_pick_the_target_and_format() {
+        let Ok(super::Command::Capture {
+            profile,
+            window,
+            mode,
+            out,
+            format,
+        }) = parse("capture --profile win --window 原神 --out shots --format jpg")
+        else {
+            panic!("expected a capture");
+        };
+        assert_eq!(profile.as_deref(), Some("win"));
+        assert_eq!(window.as_deref(), Some("原神"));
+        assert!(matches!(mode, Some(rayshot::capture::CaptureMode::Window)));
+        assert_eq!(out, Some(std::path::PathBuf::from("shots")));
+        assert_eq!(format, Some(rayshot::storage::OutputFormat::Jpeg));
+
+        // the last target given wins
+        let Ok(super::Command::Capture { mode, .. }) = parse("capture --window a --monitor DP")
+        else {
+            panic!("expected a capture");
+        };
+        let Some(rayshot::capture::CaptureMode::Monitor { monitor }) = mode else {
+            panic!("expected a monitor capture");
+        };
+        assert_eq!(monitor.as_deref(), Some("DP"));
+        let Ok(super::Command::Capture { mode, .. }) = parse("capture --all-monitors") else {
+            panic!("expected a capture");
+        };
+        assert!(matches!(
+            mode,
+            Some(rayshot::capture::CaptureMode::AllMonitors)
+        ));
+    }
+
+    #[test]
+    fn edits_keep_their_order_and_share_one_filter() {
+        let Ok(super::Command::Edit { file, edits }) = parse(
+            "edit a.png --resize 640x360 --crop 1,2,30,40 --filter nearest --rotate 90 --aspect 16:9 --flip vertical",
+        ) else {
+            panic!("expected an edit");
+        };
+        assert_eq!(file, "a.png");
+        assert_eq!(
+            edits,
+            [
+                rayshot::edit::Edit::Resize {
+                    width: 640,
+                    height: 360,
+                    filter: rayshot::edit::ResizeFilter::Nearest,
+                },
+                rayshot::edit::Edit::Crop {
+                    x: 1,
+                    y: 2,
+                    width: 30,
+                    height: 40,
+                },
+                rayshot::edit::Edit::Rotate(rayshot::edit::Rotation::Quarter),
+                rayshot::edit::Edit::AspectRatio {
+                    width: 16,
+                    height: 9,
+                },
+                rayshot::edit::Edit::FlipVertical,
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_arguments_are_rejected() {
+        for args in [
+            "",
+            "snap",
+            "capture --profile",
+            "capture --format gif",
+            "capture --all",
+            "trash",
+            "trash a.png b.png",
+            "list-windows now",
+            "purge-trash --all",
+            "edit a.png",
+            "edit a.png --crop 1,2,3",
+            "edit a.png --resize 640x-1",
+            "edit a.png --rotate 45",
+            "edit a.png --flip diagonal",
+            "edit a.png --filter bicubic --rotate 90",
+            "edit a.png --rotate",
+        ] {
+            assert!(parse(args).is_err(), "{}", args);
+        }
+        assert!(
+            matches!(parse("restore a.png"), Ok(super::Command::Restore(file)) if file == "a.png")
+        );
+        assert!(matches!(parse("-h"), Ok(super::Command::Help)));
+    }
+}
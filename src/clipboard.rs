@@ -0,0 +1,176 @@
+// the system clipboard, kept open in `RayshotState::clipboard` because on X11 and Wayland the
+// copied content is served by the process that copied it
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardContent {
+    // the decoded pixels, pasted as an image
+    Image,
+    // the absolute path as text
+    Path,
+    // a `file://` URI as text, which file managers paste as the file
+    Uri,
+}
+
+impl ClipboardContent {
+    pub const ALL: [ClipboardContent; 3] = [
+        ClipboardContent::Image,
+        ClipboardContent::Path,
+        ClipboardContent::Uri,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClipboardContent::Image => "📋 Copy Image",
+            ClipboardContent::Path => "📋 Copy Absolute Path",
+            ClipboardContent::Uri => "🔗 Copy as file URI",
+        }
+    }
+}
+
+// RFC 8089, `path` must be absolute, Windows drives become `file:///C:/...` and shares
+// `file://server/share/...`
+pub fn file_uri(path: &std::path::Path) -> String {
+    if !cfg!(windows) {
+        return format!("file://{}", uri_encode(path.as_os_str().as_encoded_bytes()));
+    }
+    windows_file_uri(&path.to_string_lossy())
+}
+
+// `file_uri` of a Windows path, apart so it can be tested anywhere
+fn windows_file_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    // `std::path::absolute` may hand out verbatim `\\?\` paths
+    let path = match path.strip_prefix("//?/UNC/") {
+        Some(share) => format!("//{}", share),
+        None => path.strip_prefix("//?/").unwrap_or(&path).to_string(),
+    };
+    match path.strip_prefix("//") {
+        Some(share) => format!("file://{}", uri_encode(share.as_bytes())),
+        None => format!("file:///{}", uri_encode(path.as_bytes())),
+    }
+}
+
+// keeps what a URI path may hold as is, `:` included so drive letters survive
+fn uri_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    for byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => {
+                encoded.push(*byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+// puts `content` of the screenshot at `path` on the clipboard, `image_buffer` spares decoding the
+// file again when the pixels are at hand
+pub fn copy_blocking(
+    clipboard: &tokio::sync::Mutex<Option<arboard::Clipboard>>,
+    content: ClipboardContent,
+    path: &std::path::Path,
+    image_buffer: Option<&image::RgbaImage>,
+) -> Result<(), String> {
+    let mut clipboard = clipboard.blocking_lock();
+    let clipboard = match &mut *clipboard {
+        Some(clipboard) => clipboard,
+        None => clipboard.insert(
+            arboard::Clipboard::new().map_err(|e| format!("cannot open the clipboard: {}", e))?,
+        ),
+    };
+    let absolute_path = std::path::absolute(path).map_err(|e| e.to_string())?;
+    let result = match content {
+        ClipboardContent::Image => {
+            let decoded;
+            let image_buffer = match image_buffer {
+                Some(image_buffer) => image_buffer,
+                None => {
+                    decoded = image::open(path).map_err(|e| e.to_string())?.to_rgba8();
+                    &decoded
+                }
+            };
+            clipboard.set_image(arboard::ImageData {
+                width: image_buffer.width() as usize,
+                height: image_buffer.height() as usize,
+                bytes: std::borrow::Cow::Borrowed(image_buffer.as_raw()),
+            })
+        }
+        ClipboardContent::Path => clipboard.set_text(absolute_path.display().to_string()),
+        ClipboardContent::Uri => clipboard.set_text(file_uri(&absolute_path)),
+    };
+    result.map_err(|e| e.to_string())
+}
+
+// decodes under the entry's `file_lock`, errors are reported
+pub async fn copy_entry(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+    content: ClipboardContent,
+) {
+    let result = {
+        let _file_lock = entry.file_lock.lock().await;
+        let file_location = *entry.file_location.lock().await;
+        let task_state = rayshot_state.clone();
+        let filename = entry.filename.clone();
+        tokio::task::spawn_blocking(move || {
+            let path = file_location
+                .file_path(
+                    &task_state.config,
+                    task_state.freedesktop_trash.as_ref(),
+                    &filename,
+                )
+                .map_err(|e| e.to_string())?;
+            copy_blocking(&task_state.clipboard, content, &path, None)
+        })
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()))
+    };
+    if let Err(e) = result {
+        crate::state::report_error(
+            rayshot_state,
+            egui_ctx,
+            format!(
+                "Failed to copy '{}' to the clipboard: {}",
+                entry.filename, e
+            ),
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn windows_paths_become_file_uris() {
+        for (path, uri) in [
+            (
+                r"C:\Users\Me\Shot 1.png",
+                "file:///C:/Users/Me/Shot%201.png",
+            ),
+            (r"\\?\D:\shots\a.png", "file:///D:/shots/a.png"),
+            (r"\\server\share\a.png", "file://server/share/a.png"),
+            (r"\\?\UNC\server\share\a.png", "file://server/share/a.png"),
+            (
+                r"C:\原神\100%.png",
+                "file:///C:/%E5%8E%9F%E7%A5%9E/100%25.png",
+            ),
+        ] {
+            assert_eq!(super::windows_file_uri(path), uri);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_paths_keep_their_bytes_percent_encoded() {
+        let path = <std::ffi::OsStr as std::os::unix::ffi::OsStrExt>::from_bytes(
+            b"/home/me/Shot #1 \xff.png",
+        );
+        assert_eq!(
+            super::file_uri(std::path::Path::new(path)),
+            "file:///home/me/Shot%20%231%20%FF.png"
+        );
+    }
+}
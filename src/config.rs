@@ -0,0 +1,549 @@
+const CONFIG_FILE_NAME: &str = "rayshot.toml";
+const XDG_CONFIG_DIR_NAME: &str = "rayshot";
+const XDG_CONFIG_FILE_NAME: &str = "config.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::path::PathBuf, std::io::Error),
+    Parse(std::path::PathBuf, toml::de::Error),
+    Invalid { field: String, reason: String },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => {
+                write!(f, "Failed to read config file '{}': {}", path.display(), e)
+            }
+            ConfigError::Parse(path, e) => {
+                write!(f, "Failed to parse config file '{}': {}", path.display(), e)
+            }
+            ConfigError::Invalid { field, reason } => {
+                write!(f, "Invalid config value for '{}': {}", field, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn invalid(field: &str, reason: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid {
+        field: field.to_string(),
+        reason: reason.into(),
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HotkeyConfig {
+    pub capture_screenshot: String,
+    pub left: String,
+    pub right: String,
+    pub trash: String,
+    pub keep_burst_frame: String,
+    pub undo: String,
+    pub redo: String,
+    // `ratings[n]` gives the current screenshot n stars, from 0 up to 5, unbound by default
+    pub ratings: Vec<String>,
+    // hotkey to the tag it adds to or removes from the current screenshot
+    pub tags: std::collections::BTreeMap<String, String>,
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            capture_screenshot: "ctrl+shift+KeyP".to_string(),
+            left: "ArrowLeft".to_string(),
+            right: "ArrowRight".to_string(),
+            trash: "Delete".to_string(),
+            keep_burst_frame: "shift+Delete".to_string(),
+            undo: "ctrl+KeyZ".to_string(),
+            redo: "ctrl+KeyY".to_string(),
+            ratings: Vec::new(),
+            tags: std::collections::BTreeMap::new(),
+        }
+    }
+}
+
+// trashed files past any of the limits are deleted for good, oldest first
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrashRetention {
+    // counted from when the file was moved to the trash
+    pub max_age_days: Option<u64>,
+    pub max_total_bytes: Option<u64>,
+    pub max_count: Option<usize>,
+    pub check_interval_minutes: u64,
+}
+
+impl Default for TrashRetention {
+    fn default() -> Self {
+        Self {
+            max_age_days: None,
+            max_total_bytes: None,
+            max_count: None,
+            check_interval_minutes: 60,
+        }
+    }
+}
+
+impl TrashRetention {
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_days.is_some() || self.max_total_bytes.is_some() || self.max_count.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashBackend {
+    // moves trashed files into `trash_dir`
+    #[default]
+    Folder,
+    // the desktop's trash, where file managers can see and restore them, Linux and BSD only
+    Freedesktop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureBackend {
+    Xcap,
+    // synthetic images from an in-memory window list, for headless runs
+    Fake,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BurstConfig {
+    // the number of frames, or the most frames taken while the hotkey is held
+    pub count: u32,
+    pub interval_ms: u64,
+    // stop as soon as the capture hotkey is released
+    #[serde(default)]
+    pub while_held: bool,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CaptureProfile {
+    pub name: String,
+    #[serde(default)]
+    pub mode: crate::capture::CaptureMode,
+    // required by window captures and regions anchored to the window
+    #[serde(default)]
+    pub window: Option<crate::capture::WindowMatcher>,
+    // relative to `screenshot_dir`, captures land directly in it when unset
+    #[serde(default)]
+    pub output_subdir: Option<std::path::PathBuf>,
+    #[serde(default)]
+    pub hotkey: Option<String>,
+    // let the user drag the part of the frame to keep before it is saved, GUI only
+    #[serde(default)]
+    pub select_region: bool,
+    // every capture of this profile takes a sequence of frames, GUI only
+    #[serde(default)]
+    pub burst: Option<BurstConfig>,
+    // PNG with fast compression when unset
+    #[serde(default)]
+    pub encoder: crate::storage::EncoderConfig,
+    // applied to the captured frame before anything else sees it, region selection included
+    #[serde(default)]
+    pub redact: Vec<crate::redaction::RedactionRule>,
+    // also save the frame before redaction next to each capture as `<name>-unredacted`
+    #[serde(default)]
+    pub keep_unredacted: bool,
+}
+
+impl CaptureProfile {
+    pub fn output_dir(&self, config: &RayshotConfig) -> std::path::PathBuf {
+        match &self.output_subdir {
+            Some(subdir) => config.screenshot_dir.join(subdir),
+            None => config.screenshot_dir.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RayshotConfig {
+    pub capture_backend: CaptureBackend,
+    // the first profile is active at startup
+    pub profiles: Vec<CaptureProfile>,
+    pub screenshot_dir: std::path::PathBuf,
+    // unused with the freedesktop trash backend
+    pub trash_dir: std::path::PathBuf,
+    pub trash_backend: TrashBackend,
+    pub trash_retention: TrashRetention,
+    pub max_loaded_textures: usize,
+    // see `FilenameTemplate` for the placeholders, the extension comes from the profile's encoder
+    pub filename_template: crate::filename::FilenameTemplate,
+    // folders below the profile's output folder, e.g. `{app}/{%Y}/{%m-%d}`, flat when unset
+    pub directory_template: Option<crate::filename::DirectoryTemplate>,
+    // where bulk copies and exports go unless another folder is picked in the UI
+    pub export_dir: std::path::PathBuf,
+    // what every GUI capture puts on the clipboard once it is saved, nothing when unset
+    pub copy_after_capture: Option<crate::clipboard::ClipboardContent>,
+    // the program and its arguments that "Open with…" runs, `{path}` in an argument is replaced
+    // by the screenshot's absolute path, which is appended when no argument has it
+    pub external_editor: Option<Vec<String>>,
+    pub hotkeys: HotkeyConfig,
+}
+
+impl Default for RayshotConfig {
+    fn default() -> Self {
+        Self {
+            capture_backend: CaptureBackend::Xcap,
+            profiles: vec![CaptureProfile {
+                name: "原神".to_string(),
+                mode: crate::capture::CaptureMode::Window,
+                window: Some(crate::capture::WindowMatcher::from_title("原神")),
+                output_subdir: None,
+                hotkey: None,
+                select_region: false,
+                burst: None,
+                encoder: crate::storage::EncoderConfig::default(),
+                redact: Vec::new(),
+                keep_unredacted: false,
+            }],
+            screenshot_dir: std::path::PathBuf::from("screenshots"),
+            trash_dir: std::path::PathBuf::from("trashed"),
+            trash_backend: TrashBackend::default(),
+            trash_retention: TrashRetention::default(),
+            max_loaded_textures: 32,
+            filename_template: crate::filename::FilenameTemplate::default(),
+            directory_template: None,
+            export_dir: std::path::PathBuf::from("exports"),
+            copy_after_capture: None,
+            external_editor: None,
+            hotkeys: HotkeyConfig::default(),
+        }
+    }
+}
+
+impl RayshotConfig {
+    // the working directory wins over the XDG config dir
+    pub fn search_paths() -> Vec<std::path::PathBuf> {
+        let mut paths = vec![std::path::PathBuf::from(CONFIG_FILE_NAME)];
+        let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(std::path::PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config"))
+            });
+        if let Some(config_home) = xdg_config_home {
+            paths.push(
+                config_home
+                    .join(XDG_CONFIG_DIR_NAME)
+                    .join(XDG_CONFIG_FILE_NAME),
+            );
+        }
+        paths
+    }
+
+    // returns the defaults along with `None` when no config file exists
+    pub fn load() -> Result<(Self, Option<std::path::PathBuf>), ConfigError> {
+        for path in Self::search_paths() {
+            if path.is_file() {
+                return Ok((Self::load_from(&path)?, Some(path)));
+            }
+        }
+        Ok((Self::default(), None))
+    }
+
+    pub fn load_from(path: &std::path::Path) -> Result<Self, ConfigError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        let config: Self =
+            toml::from_str(&content).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.profiles.is_empty() {
+            return Err(invalid(
+                "profiles",
+                "at least one capture profile is required",
+            ));
+        }
+        for (i, profile) in self.profiles.iter().enumerate() {
+            let field = |name: &str| format!("profiles[{}].{}", i, name);
+            if profile.name.trim().is_empty() {
+                return Err(invalid(&field("name"), "must not be empty"));
+            }
+            if self.profiles[..i]
+                .iter()
+                .any(|other| other.name == profile.name)
+            {
+                return Err(invalid(
+                    &field("name"),
+                    format!("duplicate profile name '{}'", profile.name),
+                ));
+            }
+            profile
+                .mode
+                .validate()
+                .map_err(|reason| invalid(&field("mode"), reason))?;
+            match &profile.window {
+                Some(window) => window
+                    .validate()
+                    .map_err(|reason| invalid(&field("window"), reason))?,
+                None if profile.mode.needs_window() => {
+                    return Err(invalid(
+                        &field("window"),
+                        "is required to capture a window or a region of it",
+                    ));
+                }
+                None => {}
+            }
+            profile
+                .encoder
+                .validate()
+                .map_err(|reason| invalid(&field("encoder"), reason))?;
+            if let Some(burst) = &profile.burst {
+                if burst.count == 0 {
+                    return Err(invalid(&field("burst.count"), "must be at least 1"));
+                }
+                if burst.interval_ms == 0 {
+                    return Err(invalid(&field("burst.interval_ms"), "must be at least 1"));
+                }
+                if profile.select_region {
+                    return Err(invalid(
+                        &field("burst"),
+                        "cannot be combined with 'select_region'",
+                    ));
+                }
+            }
+            for (j, rule) in profile.redact.iter().enumerate() {
+                rule.validate()
+                    .map_err(|reason| invalid(&field(&format!("redact[{}]", j)), reason))?;
+            }
+            if let Some(subdir) = &profile.output_subdir {
+                let is_plain_relative = subdir
+                    .components()
+                    .all(|c| matches!(c, std::path::Component::Normal(_)));
+                if subdir.as_os_str().is_empty() || !is_plain_relative {
+                    return Err(invalid(
+                        &field("output_subdir"),
+                        "must be a relative path without '..'",
+                    ));
+                }
+            }
+        }
+        if self.screenshot_dir.as_os_str().is_empty() {
+            return Err(invalid("screenshot_dir", "must not be empty"));
+        }
+        if self.trash_dir.as_os_str().is_empty() {
+            return Err(invalid("trash_dir", "must not be empty"));
+        }
+        if self.screenshot_dir == self.trash_dir {
+            return Err(invalid(
+                "trash_dir",
+                "must be different from 'screenshot_dir'",
+            ));
+        }
+        if self.export_dir.as_os_str().is_empty() {
+            return Err(invalid("export_dir", "must not be empty"));
+        }
+        if self.external_editor.as_ref().is_some_and(|editor| {
+            editor
+                .first()
+                .is_none_or(|program| program.trim().is_empty())
+        }) {
+            return Err(invalid(
+                "external_editor",
+                "must start with the program to run",
+            ));
+        }
+        if self.trash_backend == TrashBackend::Freedesktop
+            && !cfg!(all(unix, not(target_os = "macos")))
+        {
+            return Err(invalid(
+                "trash_backend",
+                "'freedesktop' is only available on Linux and BSD",
+            ));
+        }
+        if self.trash_retention.check_interval_minutes == 0 {
+            return Err(invalid(
+                "trash_retention.check_interval_minutes",
+                "must be at least 1",
+            ));
+        }
+        if self.hotkeys.ratings.len() > crate::metadata::MAX_RATING as usize + 1 {
+            return Err(invalid(
+                "hotkeys.ratings",
+                format!("binds ratings 0 to {} at most", crate::metadata::MAX_RATING),
+            ));
+        }
+        for (hotkey, tag) in &self.hotkeys.tags {
+            crate::metadata::validate_tag(tag)
+                .map_err(|reason| invalid(&format!("hotkeys.tags.\"{}\"", hotkey), reason))?;
+        }
+        if self.max_loaded_textures == 0 {
+            return Err(invalid("max_loaded_textures", "must be at least 1"));
+        }
+        self.window_shortcuts()?;
+        Ok(())
+    }
+
+    pub fn profile_by_name(&self, name: &str) -> Option<(usize, &CaptureProfile)> {
+        self.profiles
+            .iter()
+            .enumerate()
+            .find(|(_, profile)| profile.name == name)
+    }
+
+    pub fn hotkey_definitions(
+        &self,
+    ) -> Result<Vec<(global_hotkey::hotkey::HotKey, crate::RayshotHotkey)>, ConfigError> {
+        let mut definitions = vec![
+            (
+                "hotkeys.capture_screenshot",
+                &self.hotkeys.capture_screenshot,
+                crate::RayshotHotkey::CaptureScreenshot,
+            ),
+            (
+                "hotkeys.left",
+                &self.hotkeys.left,
+                crate::RayshotHotkey::Left,
+            ),
+            (
+                "hotkeys.right",
+                &self.hotkeys.right,
+                crate::RayshotHotkey::Right,
+            ),
+            (
+                "hotkeys.trash",
+                &self.hotkeys.trash,
+                crate::RayshotHotkey::Trash,
+            ),
+        ];
+        let rating_fields: Vec<_> = (0..self.hotkeys.ratings.len())
+            .map(|rating| format!("hotkeys.ratings[{}]", rating))
+            .collect();
+        for (rating, hotkey) in self.hotkeys.ratings.iter().enumerate() {
+            definitions.push((
+                &rating_fields[rating],
+                hotkey,
+                crate::RayshotHotkey::Rate(rating as u8),
+            ));
+        }
+        let tag_fields: Vec<_> = self
+            .hotkeys
+            .tags
+            .keys()
+            .map(|hotkey| format!("hotkeys.tags.\"{}\"", hotkey))
+            .collect();
+        for (field, (hotkey, tag)) in tag_fields.iter().zip(&self.hotkeys.tags) {
+            definitions.push((field, hotkey, crate::RayshotHotkey::ToggleTag(tag.clone())));
+        }
+        let profile_fields: Vec<_> = (0..self.profiles.len())
+            .map(|i| format!("profiles[{}].hotkey", i))
+            .collect();
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if let Some(hotkey) = &profile.hotkey {
+                definitions.push((
+                    &profile_fields[i],
+                    hotkey,
+                    crate::RayshotHotkey::CaptureProfile(i),
+                ));
+            }
+        }
+
+        let mut hotkeys: Vec<(global_hotkey::hotkey::HotKey, crate::RayshotHotkey)> = Vec::new();
+        for (field, hotkey_str, rayshot_hotkey) in definitions {
+            let hotkey = parse_hotkey(field, hotkey_str, &hotkeys)?;
+            hotkeys.push((hotkey, rayshot_hotkey));
+        }
+        Ok(hotkeys)
+    }
+
+    // shortcuts that only act while the window has focus, written like the global hotkeys, so
+    // the ones other apps use are not taken away from them
+    pub fn window_shortcuts(
+        &self,
+    ) -> Result<Vec<(eframe::egui::KeyboardShortcut, crate::RayshotHotkey)>, ConfigError> {
+        let definitions = [
+            (
+                "hotkeys.keep_burst_frame",
+                &self.hotkeys.keep_burst_frame,
+                crate::RayshotHotkey::KeepBurstFrame,
+            ),
+            (
+                "hotkeys.undo",
+                &self.hotkeys.undo,
+                crate::RayshotHotkey::Undo,
+            ),
+            (
+                "hotkeys.redo",
+                &self.hotkeys.redo,
+                crate::RayshotHotkey::Redo,
+            ),
+        ];
+        // a global hotkey would fire before the window sees the key
+        let mut hotkeys = self.hotkey_definitions()?;
+        let mut shortcuts = Vec::new();
+        for (field, hotkey_str, rayshot_hotkey) in definitions {
+            let hotkey = parse_hotkey(field, hotkey_str, &hotkeys)?;
+            let shortcut = window_shortcut(&hotkey).ok_or_else(|| {
+                invalid(
+                    field,
+                    format!("'{}' cannot be used inside the window", hotkey_str),
+                )
+            })?;
+            hotkeys.push((hotkey, rayshot_hotkey.clone()));
+            shortcuts.push((shortcut, rayshot_hotkey));
+        }
+        // egui ignores extra shift and alt, so e.g. shift+Delete is checked before Delete
+        shortcuts.sort_by_key(|(shortcut, _)| {
+            let modifiers = shortcut.modifiers;
+            std::cmp::Reverse(modifiers.alt as u8 + modifiers.shift as u8)
+        });
+        Ok(shortcuts)
+    }
+}
+
+fn parse_hotkey(
+    field: &str,
+    hotkey_str: &str,
+    bound: &[(global_hotkey::hotkey::HotKey, crate::RayshotHotkey)],
+) -> Result<global_hotkey::hotkey::HotKey, ConfigError> {
+    let hotkey: global_hotkey::hotkey::HotKey = hotkey_str
+        .parse()
+        .map_err(|e| invalid(field, format!("'{}': {}", hotkey_str, e)))?;
+    if let Some((_, other)) = bound.iter().find(|(other, _)| other.id() == hotkey.id()) {
+        return Err(invalid(
+            field,
+            format!("'{}' is already bound to {:?}", hotkey_str, other),
+        ));
+    }
+    Ok(hotkey)
+}
+
+// egui has no super key outside macOS, where it is the command key
+fn window_shortcut(
+    hotkey: &global_hotkey::hotkey::HotKey,
+) -> Option<eframe::egui::KeyboardShortcut> {
+    let has = |modifier| hotkey.mods.contains(modifier);
+    let super_key = has(global_hotkey::hotkey::Modifiers::SUPER);
+    if super_key && !cfg!(target_os = "macos") {
+        return None;
+    }
+    // `KeyZ` and `Digit1` are `Z` and `1` to egui, the other names match
+    let code = hotkey.key.to_string();
+    let name = code
+        .strip_prefix("Key")
+        .or_else(|| code.strip_prefix("Digit"))
+        .unwrap_or(&code);
+    let modifiers = eframe::egui::Modifiers {
+        alt: has(global_hotkey::hotkey::Modifiers::ALT),
+        ctrl: has(global_hotkey::hotkey::Modifiers::CONTROL),
+        shift: has(global_hotkey::hotkey::Modifiers::SHIFT),
+        mac_cmd: super_key,
+        command: super_key,
+    };
+    Some(eframe::egui::KeyboardShortcut::new(
+        modifiers,
+        eframe::egui::Key::from_name(name)?,
+    ))
+}
@@ -0,0 +1,422 @@
+// crops, rotations, flips and resizes that rewrite a screenshot's file, the version before each
+// edit is kept so undo can bring it back
+
+// the width to height ratios offered for crops
+pub const ASPECT_PRESETS: [(u32, u32); 6] = [(16, 9), (16, 10), (4, 3), (3, 2), (1, 1), (9, 16)];
+
+// clockwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Quarter,
+    Half,
+    ThreeQuarters,
+}
+
+impl Rotation {
+    pub fn degrees(&self) -> u32 {
+        match self {
+            Rotation::Quarter => 90,
+            Rotation::Half => 180,
+            Rotation::ThreeQuarters => 270,
+        }
+    }
+}
+
+impl std::str::FromStr for Rotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "90" => Ok(Rotation::Quarter),
+            "180" => Ok(Rotation::Half),
+            "270" => Ok(Rotation::ThreeQuarters),
+            _ => Err(format!("Unknown rotation '{}', expected 90, 180 or 270", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    #[default]
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    pub const ALL: [ResizeFilter; 5] = [
+        ResizeFilter::Nearest,
+        ResizeFilter::Triangle,
+        ResizeFilter::CatmullRom,
+        ResizeFilter::Gaussian,
+        ResizeFilter::Lanczos3,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResizeFilter::Nearest => "nearest",
+            ResizeFilter::Triangle => "triangle",
+            ResizeFilter::CatmullRom => "catmullrom",
+            ResizeFilter::Gaussian => "gaussian",
+            ResizeFilter::Lanczos3 => "lanczos3",
+        }
+    }
+}
+
+impl std::str::FromStr for ResizeFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ResizeFilter::ALL
+            .into_iter()
+            .find(|filter| filter.name() == s.to_lowercase())
+            .ok_or_else(|| {
+                format!(
+                    "Unknown resize filter '{}', expected nearest, triangle, catmullrom, gaussian or lanczos3",
+                    s
+                )
+            })
+    }
+}
+
+impl From<ResizeFilter> for image::imageops::FilterType {
+    fn from(filter: ResizeFilter) -> Self {
+        match filter {
+            ResizeFilter::Nearest => image::imageops::FilterType::Nearest,
+            ResizeFilter::Triangle => image::imageops::FilterType::Triangle,
+            ResizeFilter::CatmullRom => image::imageops::FilterType::CatmullRom,
+            ResizeFilter::Gaussian => image::imageops::FilterType::Gaussian,
+            ResizeFilter::Lanczos3 => image::imageops::FilterType::Lanczos3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    // in pixels of the image the edit is applied to
+    Crop {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    // the largest centered crop with this width to height ratio
+    AspectRatio {
+        width: u32,
+        height: u32,
+    },
+    Rotate(Rotation),
+    FlipHorizontal,
+    FlipVertical,
+    Resize {
+        width: u32,
+        height: u32,
+        filter: ResizeFilter,
+    },
+}
+
+impl Edit {
+    pub fn label(&self) -> String {
+        match self {
+            Edit::Crop {
+                x,
+                y,
+                width,
+                height,
+            } => format!("Crop {}×{} at {},{}", width, height, x, y),
+            Edit::AspectRatio { width, height } => format!("Crop to {}:{}", width, height),
+            Edit::Rotate(rotation) => format!("Rotate {}°", rotation.degrees()),
+            Edit::FlipHorizontal => "Flip horizontally".to_string(),
+            Edit::FlipVertical => "Flip vertically".to_string(),
+            Edit::Resize {
+                width,
+                height,
+                filter,
+            } => format!("Resize to {}×{} ({})", width, height, filter.name()),
+        }
+    }
+
+    // the `(x, y, width, height)` a crop keeps of a `width`×`height` image
+    fn crop_rect(&self, width: u32, height: u32) -> Result<Option<(u32, u32, u32, u32)>, String> {
+        match *self {
+            Edit::Crop {
+                x,
+                y,
+                width: crop_width,
+                height: crop_height,
+            } => {
+                if crop_width == 0
+                    || crop_height == 0
+                    || x.saturating_add(crop_width) > width
+                    || y.saturating_add(crop_height) > height
+                {
+                    return Err(format!(
+                        "{} does not fit in the {}×{} image",
+                        self.label(),
+                        width,
+                        height
+                    ));
+                }
+                Ok(Some((x, y, crop_width, crop_height)))
+            }
+            Edit::AspectRatio {
+                width: ratio_width,
+                height: ratio_height,
+            } => {
+                if ratio_width == 0 || ratio_height == 0 {
+                    return Err(format!(
+                        "Invalid aspect ratio {}:{}",
+                        ratio_width, ratio_height
+                    ));
+                }
+                let (width_64, height_64) = (width as u64, height as u64);
+                let (crop_width, crop_height) =
+                    if width_64 * ratio_height as u64 > height_64 * ratio_width as u64 {
+                        (
+                            height_64 * ratio_width as u64 / ratio_height as u64,
+                            height_64,
+                        )
+                    } else {
+                        (
+                            width_64,
+                            width_64 * ratio_height as u64 / ratio_width as u64,
+                        )
+                    };
+                let (crop_width, crop_height) =
+                    ((crop_width as u32).max(1), (crop_height as u32).max(1));
+                Ok(Some((
+                    (width - crop_width) / 2,
+                    (height - crop_height) / 2,
+                    crop_width,
+                    crop_height,
+                )))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // the size of a `width`×`height` image after the edit
+    pub fn output_size(&self, width: u32, height: u32) -> Result<(u32, u32), String> {
+        if let Some((_, _, crop_width, crop_height)) = self.crop_rect(width, height)? {
+            return Ok((crop_width, crop_height));
+        }
+        match *self {
+            Edit::Rotate(Rotation::Quarter | Rotation::ThreeQuarters) => Ok((height, width)),
+            Edit::Resize { width: 0, .. } | Edit::Resize { height: 0, .. } => {
+                Err(format!("{} is empty", self.label()))
+            }
+            Edit::Resize {
+                width: new_width,
+                height: new_height,
+                ..
+            } => Ok((new_width, new_height)),
+            _ => Ok((width, height)),
+        }
+    }
+
+    pub fn apply(&self, image_buffer: &image::RgbaImage) -> Result<image::RgbaImage, String> {
+        let (width, height) = image_buffer.dimensions();
+        if let Some((x, y, crop_width, crop_height)) = self.crop_rect(width, height)? {
+            return Ok(
+                image::imageops::crop_imm(image_buffer, x, y, crop_width, crop_height).to_image(),
+            );
+        }
+        self.output_size(width, height)?;
+        Ok(match *self {
+            Edit::Rotate(Rotation::Quarter) => image::imageops::rotate90(image_buffer),
+            Edit::Rotate(Rotation::Half) => image::imageops::rotate180(image_buffer),
+            Edit::Rotate(Rotation::ThreeQuarters) => image::imageops::rotate270(image_buffer),
+            Edit::FlipHorizontal => image::imageops::flip_horizontal(image_buffer),
+            Edit::FlipVertical => image::imageops::flip_vertical(image_buffer),
+            Edit::Resize {
+                width,
+                height,
+                filter,
+            } => image::imageops::resize(image_buffer, width, height, filter.into()),
+            Edit::Crop { .. } | Edit::AspectRatio { .. } => unreachable!(),
+        })
+    }
+
+    // moves an annotation made on a `width`×`height` image to where the edit puts its pixels
+    fn transform(
+        &self,
+        annotation: &mut crate::annotation::Annotation,
+        width: u32,
+        height: u32,
+    ) -> Result<(), String> {
+        let (width, height) = (width as f32, height as f32);
+        let (x, y) = match self.crop_rect(width as u32, height as u32)? {
+            Some((x, y, _, _)) => (x as f32, y as f32),
+            None => (0.0, 0.0),
+        };
+        let (new_width, new_height) = self.output_size(width as u32, height as u32)?;
+        let (scale_x, scale_y) = (new_width as f32 / width, new_height as f32 / height);
+        let edit = *self;
+        annotation.shape.map_points(|[px, py]| match edit {
+            Edit::Crop { .. } | Edit::AspectRatio { .. } => [px - x, py - y],
+            Edit::Rotate(Rotation::Quarter) => [height - py, px],
+            Edit::Rotate(Rotation::Half) => [width - px, height - py],
+            Edit::Rotate(Rotation::ThreeQuarters) => [py, width - px],
+            Edit::FlipHorizontal => [width - px, py],
+            Edit::FlipVertical => [px, height - py],
+            Edit::Resize { .. } => [px * scale_x, py * scale_y],
+        });
+        if matches!(self, Edit::Resize { .. }) {
+            annotation.size *= (scale_x * scale_y).sqrt();
+        }
+        Ok(())
+    }
+}
+
+// applies the edits in order
+pub fn apply_all(
+    image_buffer: &image::RgbaImage,
+    edits: &[Edit],
+) -> Result<image::RgbaImage, String> {
+    let mut edited = image_buffer.clone();
+    for edit in edits {
+        edited = edit.apply(&edited)?;
+    }
+    Ok(edited)
+}
+
+// the annotations of a `width`×`height` image moved along with the edits
+pub fn transform_annotations(
+    annotations: &[crate::annotation::Annotation],
+    edits: &[Edit],
+    width: u32,
+    height: u32,
+) -> Result<Vec<crate::annotation::Annotation>, String> {
+    let mut annotations = annotations.to_vec();
+    let (mut width, mut height) = (width, height);
+    for edit in edits {
+        for annotation in &mut annotations {
+            edit.transform(annotation, width, height)?;
+        }
+        (width, height) = edit.output_size(width, height)?;
+    }
+    Ok(annotations)
+}
+
+#[cfg(test)]
+mod tests {
+    fn callout(at: [f32; 2], size: f32) -> crate::annotation::Annotation {
+        crate::annotation::Annotation {
+            shape: crate::annotation::Shape::Callout { at, number: 1 },
+            color: [255, 0, 0, 255],
+            size,
+        }
+    }
+
+    fn callout_at(annotation: &crate::annotation::Annotation) -> [f32; 2] {
+        match annotation.shape {
+            crate::annotation::Shape::Callout { at, .. } => at,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn annotations_stay_on_the_pixels_they_were_made_on() {
+        let (width, height) = (7, 5);
+        let marked = (4, 1);
+        let image_buffer = image::RgbaImage::from_fn(width, height, |x, y| {
+            image::Rgba([0, 0, 0, if (x, y) == marked { 255 } else { 0 }])
+        });
+        let annotation = callout([marked.0 as f32 + 0.5, marked.1 as f32 + 0.5], 1.0);
+        let edits = [
+            super::Edit::Crop {
+                x: 1,
+                y: 0,
+                width: 5,
+                height: 4,
+            },
+            super::Edit::Rotate(super::Rotation::Quarter),
+            super::Edit::FlipHorizontal,
+            super::Edit::Rotate(super::Rotation::Half),
+            super::Edit::FlipVertical,
+            super::Edit::Rotate(super::Rotation::ThreeQuarters),
+            super::Edit::AspectRatio {
+                width: 1,
+                height: 1,
+            },
+        ];
+        for count in 1..=edits.len() {
+            let edited = super::apply_all(&image_buffer, &edits[..count]).unwrap();
+            let moved = super::transform_annotations(
+                std::slice::from_ref(&annotation),
+                &edits[..count],
+                width,
+                height,
+            )
+            .unwrap();
+            let [x, y] = callout_at(&moved[0]);
+            assert_eq!(
+                edited.get_pixel(x as u32, y as u32).0[3],
+                255,
+                "after {:?}",
+                &edits[..count]
+            );
+        }
+    }
+
+    #[test]
+    fn resizing_scales_annotation_points_and_sizes() {
+        let edits = [super::Edit::Resize {
+            width: 40,
+            height: 10,
+            filter: super::ResizeFilter::Nearest,
+        }];
+        let moved =
+            super::transform_annotations(&[callout([5.0, 5.0], 3.0)], &edits, 10, 10).unwrap();
+        assert_eq!(callout_at(&moved[0]), [20.0, 5.0]);
+        assert_eq!(moved[0].size, 6.0);
+        let resized = super::apply_all(&image::RgbaImage::new(10, 10), &edits).unwrap();
+        assert_eq!(resized.dimensions(), (40, 10));
+    }
+
+    #[test]
+    fn aspect_ratio_crops_keep_the_largest_centered_rect() {
+        let edit = super::Edit::AspectRatio {
+            width: 16,
+            height: 9,
+        };
+        assert_eq!(edit.crop_rect(1920, 1200), Ok(Some((0, 60, 1920, 1080))));
+        assert_eq!(edit.crop_rect(2560, 1080), Ok(Some((320, 0, 1920, 1080))));
+        // never empty, however thin the image
+        assert_eq!(edit.crop_rect(1, 1), Ok(Some((0, 0, 1, 1))));
+    }
+
+    #[test]
+    fn edits_that_leave_nothing_are_rejected() {
+        let image_buffer = image::RgbaImage::new(10, 10);
+        let crop = |x, y, width, height| super::Edit::Crop {
+            x,
+            y,
+            width,
+            height,
+        };
+        for edit in [
+            crop(0, 0, 0, 5),
+            crop(5, 0, 6, 5),
+            crop(0, u32::MAX, 1, 1),
+            super::Edit::AspectRatio {
+                width: 0,
+                height: 1,
+            },
+            super::Edit::Resize {
+                width: 0,
+                height: 10,
+                filter: super::ResizeFilter::Lanczos3,
+            },
+        ] {
+            assert!(edit.apply(&image_buffer).is_err(), "{:?}", edit);
+            assert!(
+                super::transform_annotations(&[callout([1.0, 1.0], 1.0)], &[edit], 10, 10).is_err(),
+                "{:?}",
+                edit
+            );
+        }
+    }
+}
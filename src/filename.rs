@@ -0,0 +1,389 @@
+// longest stem rendered from a template, leaves room for the suffix and extension
+const MAX_STEM_BYTES: usize = 200;
+
+// characters that are illegal in a file name on Windows or Linux
+const ILLEGAL_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+// device names Windows refuses as a file stem, whatever the extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    App,
+    Title,
+    Profile,
+    // a chrono format of the capture time, including the leading '%'
+    Time(String),
+    Counter { width: usize },
+    Width,
+    Height,
+    Hash,
+}
+
+// `{app}`, `{title}`, `{profile}`, `{%chrono}`, `{counter}` (`{counter:4}` zero-pads),
+// `{width}`, `{height}` and `{hash}` separated by literal text
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct FilenameTemplate {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl FilenameTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let segments = parse_segments(template)?;
+        if !segments.iter().any(|segment| {
+            matches!(
+                segment,
+                Segment::Time(_) | Segment::Counter { .. } | Segment::Hash
+            )
+        }) {
+            return Err(
+                "must contain a time, '{counter}' or '{hash}' placeholder so filenames stay unique"
+                    .to_string(),
+            );
+        }
+        Ok(Self {
+            source: template.to_string(),
+            segments,
+        })
+    }
+
+    pub fn needs_image(&self) -> bool {
+        needs_image(&self.segments)
+    }
+
+    pub fn uses_counter(&self) -> bool {
+        uses_counter(&self.segments)
+    }
+
+    // the file stem, every substituted value is sanitized
+    pub fn render(&self, context: &FilenameContext) -> String {
+        finish_stem(render_segments(&self.segments, context))
+    }
+}
+
+impl Default for FilenameTemplate {
+    fn default() -> Self {
+        Self::parse("{app}_{%Y%m%d_%H%M%S.%f}").expect("the default template is valid")
+    }
+}
+
+impl TryFrom<String> for FilenameTemplate {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        Self::parse(&template)
+    }
+}
+
+impl std::fmt::Display for FilenameTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+// the same placeholders as `FilenameTemplate`, one template per '/'-separated folder
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(try_from = "String")]
+pub struct DirectoryTemplate {
+    source: String,
+    components: Vec<Vec<Segment>>,
+}
+
+impl DirectoryTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let components = template
+            .split('/')
+            .map(|component| match component {
+                "" | "." | ".." => Err(format!(
+                    "'{}' has an empty, '.' or '..' folder, expected a relative path like '{{app}}/{{%Y}}'",
+                    template
+                )),
+                _ => parse_segments(component),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            source: template.to_string(),
+            components,
+        })
+    }
+
+    pub fn needs_image(&self) -> bool {
+        self.components.iter().any(|segments| needs_image(segments))
+    }
+
+    pub fn uses_counter(&self) -> bool {
+        self.components
+            .iter()
+            .any(|segments| uses_counter(segments))
+    }
+
+    pub fn render(&self, context: &FilenameContext) -> std::path::PathBuf {
+        self.components
+            .iter()
+            .map(|segments| finish_stem(render_segments(segments, context)))
+            .collect()
+    }
+}
+
+impl TryFrom<String> for DirectoryTemplate {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        Self::parse(&template)
+    }
+}
+
+impl std::fmt::Display for DirectoryTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn parse_segments(template: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            return Err("unclosed '{'".to_string());
+        };
+        if start > 0 {
+            segments.push(Segment::Literal(parse_literal(&rest[..start])?));
+        }
+        segments.push(parse_placeholder(&rest[start + 1..start + len])?);
+        rest = &rest[start + len + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Literal(parse_literal(rest)?));
+    }
+    Ok(segments)
+}
+
+// `{width}`, `{height}` and `{hash}` are only known once the frame is captured
+fn needs_image(segments: &[Segment]) -> bool {
+    segments
+        .iter()
+        .any(|segment| matches!(segment, Segment::Width | Segment::Height | Segment::Hash))
+}
+
+fn uses_counter(segments: &[Segment]) -> bool {
+    segments
+        .iter()
+        .any(|segment| matches!(segment, Segment::Counter { .. }))
+}
+
+fn render_segments(segments: &[Segment], context: &FilenameContext) -> String {
+    let mut stem = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Literal(text) => stem.push_str(text),
+            Segment::App => stem.push_str(&sanitize(context.app)),
+            Segment::Title => stem.push_str(&sanitize(context.title)),
+            Segment::Profile => stem.push_str(&sanitize(context.profile)),
+            Segment::Time(time_format) => {
+                stem.push_str(&sanitize(&context.time.format(time_format).to_string()))
+            }
+            Segment::Counter { width } => {
+                stem.push_str(&format!("{:0width$}", context.counter, width = *width))
+            }
+            Segment::Width => {
+                stem.push_str(&context.image.map_or(0, |image| image.width()).to_string())
+            }
+            Segment::Height => {
+                stem.push_str(&context.image.map_or(0, |image| image.height()).to_string())
+            }
+            Segment::Hash => stem.push_str(&context.image.map_or_else(
+                || "0".repeat(8),
+                |image| content_hash(image)[..8].to_string(),
+            )),
+        }
+    }
+    stem
+}
+
+pub struct FilenameContext<'a> {
+    pub app: &'a str,
+    pub title: &'a str,
+    pub profile: &'a str,
+    pub time: chrono::DateTime<chrono::Local>,
+    pub counter: u64,
+    // `None` while the frame is still being captured
+    pub image: Option<&'a image::RgbaImage>,
+}
+
+fn parse_literal(text: &str) -> Result<String, String> {
+    if text
+        .chars()
+        .any(|c| ILLEGAL_CHARS.contains(&c) || c.is_control())
+    {
+        return Err(format!(
+            "'{}' contains characters that are not allowed in filenames ({} or control characters)",
+            text,
+            ILLEGAL_CHARS.iter().collect::<String>()
+        ));
+    }
+    Ok(text.to_string())
+}
+
+fn parse_placeholder(placeholder: &str) -> Result<Segment, String> {
+    if placeholder.starts_with('%') {
+        let has_error = chrono::format::StrftimeItems::new(placeholder)
+            .any(|item| matches!(item, chrono::format::Item::Error));
+        if has_error {
+            return Err(format!("invalid time format '{{{}}}'", placeholder));
+        }
+        return Ok(Segment::Time(placeholder.to_string()));
+    }
+    match placeholder.split_once(':') {
+        Some(("counter", width)) => width
+            .parse()
+            .ok()
+            .filter(|width| (1..=20).contains(width))
+            .map(|width| Segment::Counter { width })
+            .ok_or_else(|| {
+                format!(
+                    "invalid counter width in '{{{}}}', expected 1 to 20",
+                    placeholder
+                )
+            }),
+        Some(_) => Err(format!("unknown placeholder '{{{}}}'", placeholder)),
+        None => match placeholder {
+            "app" => Ok(Segment::App),
+            "title" => Ok(Segment::Title),
+            "profile" => Ok(Segment::Profile),
+            "counter" => Ok(Segment::Counter { width: 1 }),
+            "width" => Ok(Segment::Width),
+            "height" => Ok(Segment::Height),
+            "hash" => Ok(Segment::Hash),
+            _ => Err(format!("unknown placeholder '{{{}}}'", placeholder)),
+        },
+    }
+}
+
+// replaces the characters a filesystem would reject, so window titles can go into names
+pub fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if ILLEGAL_CHARS.contains(&c) || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+// a name typed by the user, made as safe as the rendered ones
+pub fn user_stem(stem: &str) -> String {
+    finish_stem(sanitize(stem.trim()))
+}
+
+// Windows drops trailing dots and spaces and refuses device names, a leading dot would hide the
+// file or folder from the gallery scan
+fn finish_stem(mut stem: String) -> String {
+    if stem.len() > MAX_STEM_BYTES {
+        let mut end = MAX_STEM_BYTES;
+        while !stem.is_char_boundary(end) {
+            end -= 1;
+        }
+        stem.truncate(end);
+    }
+    let trimmed_len = stem.trim_end_matches(['.', ' ']).len();
+    stem.truncate(trimmed_len);
+    if stem.is_empty() {
+        return "_".to_string();
+    }
+    let device_name = stem.split('.').next().unwrap_or_default();
+    if stem.starts_with('.')
+        || RESERVED_NAMES
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(device_name))
+    {
+        stem.insert(0, '_');
+    }
+    stem
+}
+
+// FNV-1a over the dimensions and pixels, stable across builds
+pub fn content_hash(image: &image::RgbaImage) -> String {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+    let mut hash = FNV_OFFSET;
+    let dimensions = [image.width().to_le_bytes(), image.height().to_le_bytes()];
+    for byte in dimensions.iter().flatten().chain(image.as_raw()) {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    fn render(template: &str, app: &str) -> String {
+        let context = super::FilenameContext {
+            app,
+            title: "Title",
+            profile: "default",
+            time: chrono::TimeZone::with_ymd_and_hms(&chrono::Local, 2024, 5, 6, 7, 8, 9).unwrap(),
+            counter: 42,
+            image: None,
+        };
+        super::FilenameTemplate::parse(template)
+            .unwrap()
+            .render(&context)
+    }
+
+    #[test]
+    fn parse_rejects_templates_that_cannot_name_files() {
+        for template in [
+            "{app}",
+            "{app}_{%Y",
+            "{nope}_{counter}",
+            "{counter:0}",
+            "{counter:21}",
+            "a:b_{counter}",
+            "{%Q}",
+        ] {
+            assert!(
+                super::FilenameTemplate::parse(template).is_err(),
+                "{}",
+                template
+            );
+        }
+        assert!(super::FilenameTemplate::parse("{app}_{counter:4}").is_ok());
+    }
+
+    #[test]
+    fn render_substitutes_and_sanitizes_the_placeholders() {
+        assert_eq!(
+            render("{app}_{%Y%m%d}_{counter:4}", "a/b"),
+            "a_b_20240506_0042"
+        );
+        assert_eq!(
+            render("{title}-{profile}-{hash}", "app"),
+            "Title-default-00000000"
+        );
+    }
+
+    #[test]
+    fn render_prefixes_reserved_names_and_leading_dots() {
+        assert_eq!(render("{app}.{counter}", "con"), "_con.42");
+        assert_eq!(render("{app}.{counter}", "lpt1"), "_lpt1.42");
+        assert_eq!(render("{app}_{counter}", "Console"), "Console_42");
+        assert_eq!(render("{app}{counter}", ".hidden"), "_.hidden42");
+        assert_eq!(render("{counter}{app}", "x. ."), "42x");
+    }
+
+    #[test]
+    fn render_truncates_long_stems_at_a_char_boundary() {
+        let stem = render("{counter:3}{app}", &"é".repeat(150));
+        assert_eq!(stem.len(), super::MAX_STEM_BYTES - 1);
+        assert_eq!(stem, format!("042{}", "é".repeat(98)));
+    }
+}
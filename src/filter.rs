@@ -0,0 +1,248 @@
+// what the gallery shows, Left and Right skip the entries it hides
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GalleryFilter {
+    // `None` shows both
+    pub location: Option<crate::FileLocation>,
+    pub failed_only: bool,
+    // inclusive, by capture day
+    pub captured_from: Option<chrono::NaiveDate>,
+    pub captured_to: Option<chrono::NaiveDate>,
+    // substrings, ignoring case
+    pub app_name: String,
+    pub filename: String,
+    // one of the entry's tags, ignoring case
+    pub tag: String,
+    pub min_rating: u8,
+    pub min_width: Option<u32>,
+    pub min_height: Option<u32>,
+}
+
+impl GalleryFilter {
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    // criteria that need the index hide entries without a record
+    pub fn matches(
+        &self,
+        filename: &str,
+        location: crate::FileLocation,
+        failed: bool,
+        metadata: Option<&crate::metadata::ScreenshotMetadata>,
+    ) -> bool {
+        if self.location.is_some_and(|wanted| wanted != location) {
+            return false;
+        }
+        if self.failed_only && !failed {
+            return false;
+        }
+        if !contains_ignore_case(filename, &self.filename) {
+            return false;
+        }
+        let needs_metadata = self.captured_from.is_some()
+            || self.captured_to.is_some()
+            || !self.app_name.trim().is_empty()
+            || !self.tag.trim().is_empty()
+            || self.min_rating > 0
+            || self.min_width.is_some()
+            || self.min_height.is_some();
+        if !needs_metadata {
+            return true;
+        }
+        let Some(metadata) = metadata else {
+            return false;
+        };
+
+        let captured_on = metadata.captured_at.map(|captured_at| captured_at.date());
+        if let Some(captured_from) = self.captured_from {
+            if captured_on.is_none_or(|day| day < captured_from) {
+                return false;
+            }
+        }
+        if let Some(captured_to) = self.captured_to {
+            if captured_on.is_none_or(|day| day > captured_to) {
+                return false;
+            }
+        }
+        if !self.app_name.trim().is_empty()
+            && !metadata
+                .app_name
+                .as_deref()
+                .is_some_and(|app_name| contains_ignore_case(app_name, &self.app_name))
+        {
+            return false;
+        }
+        let tag = self.tag.trim().to_lowercase();
+        if !tag.is_empty()
+            && !metadata
+                .tags
+                .iter()
+                .any(|other| other.to_lowercase() == tag)
+        {
+            return false;
+        }
+        if metadata.rating < self.min_rating {
+            return false;
+        }
+        if self
+            .min_width
+            .is_some_and(|min_width| metadata.width.is_none_or(|width| width < min_width))
+        {
+            return false;
+        }
+        if self
+            .min_height
+            .is_some_and(|min_height| metadata.height.is_none_or(|height| height < min_height))
+        {
+            return false;
+        }
+        true
+    }
+
+    // for the async side, which may wait for the entry's locks
+    pub async fn matches_entry(
+        &self,
+        entry: &crate::ScreenshotEntry,
+        metadata: &crate::metadata::MetadataIndex,
+    ) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+        let location = *entry.file_location.lock().await;
+        let failed = entry.state.lock().await.failed;
+        self.matches(
+            &entry.filename,
+            location,
+            failed,
+            metadata.get(&entry.filename),
+        )
+    }
+}
+
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    let needle = needle.trim();
+    needle.is_empty() || haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    fn metadata() -> crate::metadata::ScreenshotMetadata {
+        crate::metadata::ScreenshotMetadata {
+            captured_at: chrono::NaiveDate::from_ymd_opt(2024, 5, 6)
+                .and_then(|day| day.and_hms_opt(23, 59, 0)),
+            app_name: Some("GenshinImpact".to_string()),
+            width: Some(1920),
+            height: Some(1080),
+            tags: vec!["Boss".to_string()],
+            rating: 3,
+            ..Default::default()
+        }
+    }
+
+    fn matches(filter: &super::GalleryFilter) -> bool {
+        filter.matches(
+            "GenshinImpact_20240506.png",
+            crate::FileLocation::Local,
+            false,
+            Some(&metadata()),
+        )
+    }
+
+    #[test]
+    fn the_default_filter_matches_everything() {
+        let filter = super::GalleryFilter::default();
+        assert!(!filter.is_active());
+        assert!(filter.matches("a.png", crate::FileLocation::Trash, true, None));
+    }
+
+    #[test]
+    fn every_criterion_must_match() {
+        let day = |day| chrono::NaiveDate::from_ymd_opt(2024, 5, day);
+        let filter = super::GalleryFilter {
+            location: Some(crate::FileLocation::Local),
+            captured_from: day(6),
+            captured_to: day(6),
+            app_name: " genshin ".to_string(),
+            filename: "IMPACT_2024".to_string(),
+            tag: "boss".to_string(),
+            min_rating: 3,
+            min_width: Some(1920),
+            min_height: Some(1080),
+            ..Default::default()
+        };
+        assert!(matches(&filter));
+
+        let misses = [
+            super::GalleryFilter {
+                location: Some(crate::FileLocation::Trash),
+                ..filter.clone()
+            },
+            super::GalleryFilter {
+                failed_only: true,
+                ..filter.clone()
+            },
+            super::GalleryFilter {
+                captured_from: day(7),
+                ..filter.clone()
+            },
+            super::GalleryFilter {
+                captured_to: day(5),
+                ..filter.clone()
+            },
+            super::GalleryFilter {
+                app_name: "star rail".to_string(),
+                ..filter.clone()
+            },
+            super::GalleryFilter {
+                filename: "starrail".to_string(),
+                ..filter.clone()
+            },
+            // tags match whole, only ignoring case
+            super::GalleryFilter {
+                tag: "bos".to_string(),
+                ..filter.clone()
+            },
+            super::GalleryFilter {
+                min_rating: 4,
+                ..filter.clone()
+            },
+            super::GalleryFilter {
+                min_width: Some(1921),
+                ..filter.clone()
+            },
+            super::GalleryFilter {
+                min_height: Some(1081),
+                ..filter.clone()
+            },
+        ];
+        for filter in misses {
+            assert!(!matches(&filter), "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn criteria_from_the_index_hide_entries_without_a_record() {
+        let by_name = super::GalleryFilter {
+            filename: "a.".to_string(),
+            ..Default::default()
+        };
+        assert!(by_name.matches("a.png", crate::FileLocation::Local, false, None));
+        let by_rating = super::GalleryFilter {
+            min_rating: 1,
+            ..Default::default()
+        };
+        assert!(!by_rating.matches("a.png", crate::FileLocation::Local, false, None));
+        // unknown dimensions are below every minimum
+        let by_width = super::GalleryFilter {
+            min_width: Some(1),
+            ..Default::default()
+        };
+        let unmeasured = crate::metadata::ScreenshotMetadata::default();
+        assert!(!by_width.matches(
+            "a.png",
+            crate::FileLocation::Local,
+            false,
+            Some(&unmeasured)
+        ));
+    }
+}
@@ -0,0 +1,453 @@
+const JOURNAL_FILE_NAME: &str = ".rayshot-journal.toml";
+
+// older steps are forgotten past this many
+const MAX_JOURNAL_STEPS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase", deny_unknown_fields)]
+pub enum FileOperation {
+    // `filename` was moved out of `from` into the other location
+    Move {
+        filename: String,
+        from: crate::FileLocation,
+    },
+    // `filename` was rewritten by an edit and `backup` in the edit backup folder holds the
+    // version from before, swapping the two undoes and redoes it
+    Edit {
+        filename: String,
+        backup: String,
+        // the annotation layers of the versions before and after the edit
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        before: Vec<crate::annotation::Annotation>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        after: Vec<crate::annotation::Annotation>,
+    },
+    // `from` was renamed to `to` in the same folder and location
+    Rename {
+        from: String,
+        to: String,
+    },
+}
+
+impl FileOperation {
+    pub fn inverse(&self) -> Self {
+        match self {
+            FileOperation::Move { filename, from } => FileOperation::Move {
+                filename: filename.clone(),
+                from: match from {
+                    crate::FileLocation::Local => crate::FileLocation::Trash,
+                    crate::FileLocation::Trash => crate::FileLocation::Local,
+                },
+            },
+            FileOperation::Edit {
+                filename,
+                backup,
+                before,
+                after,
+            } => FileOperation::Edit {
+                filename: filename.clone(),
+                backup: backup.clone(),
+                before: after.clone(),
+                after: before.clone(),
+            },
+            FileOperation::Rename { from, to } => FileOperation::Rename {
+                from: to.clone(),
+                to: from.clone(),
+            },
+        }
+    }
+}
+
+// operations undone and redone together, e.g. trashing the other frames of a burst
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JournalStep {
+    pub operations: Vec<FileOperation>,
+}
+
+// deletes the edit backups only the forgotten steps could bring back, best effort
+pub fn remove_backups(config: &crate::config::RayshotConfig, forgotten: &[JournalStep]) {
+    for operation in forgotten.iter().flat_map(|step| &step.operations) {
+        if let FileOperation::Edit { backup, .. } = operation {
+            crate::storage::remove_edit_backup(config, backup);
+        }
+    }
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Journal {
+    // the last step is undone first
+    undo: Vec<JournalStep>,
+    // the last step is redone first
+    redo: Vec<JournalStep>,
+}
+
+impl Journal {
+    pub fn path(config: &crate::config::RayshotConfig) -> std::path::PathBuf {
+        config.screenshot_dir.join(JOURNAL_FILE_NAME)
+    }
+
+    pub fn load(config: &crate::config::RayshotConfig) -> std::io::Result<Self> {
+        let path = Self::path(config);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid journal file '{}': {}", path.display(), e),
+                )
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, config: &crate::config::RayshotConfig) -> std::io::Result<()> {
+        let content = toml::to_string(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        crate::storage::write_atomically(&Self::path(config), content)
+    }
+
+    // reads the journal, applies `update` and writes it back under the shared lock, so steps the
+    // GUI and the CLI record at the same time are all kept
+    pub fn update<R>(
+        config: &crate::config::RayshotConfig,
+        update: impl FnOnce(&mut Self) -> R,
+    ) -> std::io::Result<(Self, R)> {
+        let _lock = crate::storage::lock_shared_files(config)?;
+        let mut journal = Self::load(config)?;
+        let result = update(&mut journal);
+        journal.save(config)?;
+        Ok((journal, result))
+    }
+
+    // a new step makes the undone ones unreachable, returns the steps that are forgotten
+    pub fn record(&mut self, step: JournalStep) -> Vec<JournalStep> {
+        let mut forgotten: Vec<_> = self.redo.drain(..).collect();
+        forgotten.extend(self.push_undo(step));
+        forgotten
+    }
+
+    fn push_undo(&mut self, step: JournalStep) -> Option<JournalStep> {
+        self.undo.push(step);
+        (self.undo.len() > MAX_JOURNAL_STEPS).then(|| self.undo.remove(0))
+    }
+}
+
+// applies `update` to the journal on disk, which the CLI records steps in too, and keeps the
+// result, errors are reported and give `None`
+async fn update_journal<R: Send + 'static>(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    update: impl FnOnce(&mut Journal) -> R + Send + 'static,
+) -> Option<R> {
+    let mut journal = rayshot_state.journal.lock().await;
+    let config = rayshot_state.config.clone();
+    match tokio::task::spawn_blocking(move || Journal::update(&config, update))
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+    {
+        Ok((updated, result)) => {
+            *journal = updated;
+            Some(result)
+        }
+        Err(e) => {
+            drop(journal);
+            crate::state::report_error(
+                rayshot_state,
+                egui_ctx,
+                format!("Failed to save the undo journal: {}", e),
+            )
+            .await;
+            None
+        }
+    }
+}
+
+pub async fn record(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    operations: Vec<FileOperation>,
+) {
+    if operations.is_empty() {
+        return;
+    }
+    let forgotten = update_journal(rayshot_state, egui_ctx, |journal| {
+        journal.record(JournalStep { operations })
+    })
+    .await;
+    forget(rayshot_state, forgotten.unwrap_or_default()).await;
+}
+
+async fn forget(rayshot_state: &crate::RayshotState, forgotten: Vec<JournalStep>) {
+    if forgotten.is_empty() {
+        return;
+    }
+    let config = rayshot_state.config.clone();
+    let _ = tokio::task::spawn_blocking(move || remove_backups(&config, &forgotten)).await;
+}
+
+// reverts the last recorded step, only the operations that could be reverted can be redone
+pub async fn undo(rayshot_state: &crate::RayshotState, egui_ctx: &eframe::egui::Context) {
+    // the entries follow what the CLI changed first, its steps can be undone too
+    crate::metadata::save(rayshot_state, egui_ctx).await;
+    let Some(step) = update_journal(rayshot_state, egui_ctx, |journal| journal.undo.pop()).await
+    else {
+        return;
+    };
+    let Some(step) = step else {
+        return crate::state::report_error(rayshot_state, egui_ctx, "Nothing to undo".to_string())
+            .await;
+    };
+    let mut reverted = Vec::new();
+    for operation in step.operations.into_iter().rev() {
+        if apply(rayshot_state, egui_ctx, &operation.inverse()).await {
+            reverted.push(operation);
+        }
+    }
+    reverted.reverse();
+    crate::metadata::save(rayshot_state, egui_ctx).await;
+
+    if !reverted.is_empty() {
+        update_journal(rayshot_state, egui_ctx, |journal| {
+            journal.redo.push(JournalStep {
+                operations: reverted,
+            })
+        })
+        .await;
+    }
+}
+
+// applies the last undone step again
+pub async fn redo(rayshot_state: &crate::RayshotState, egui_ctx: &eframe::egui::Context) {
+    crate::metadata::save(rayshot_state, egui_ctx).await;
+    let Some(step) = update_journal(rayshot_state, egui_ctx, |journal| journal.redo.pop()).await
+    else {
+        return;
+    };
+    let Some(step) = step else {
+        return crate::state::report_error(rayshot_state, egui_ctx, "Nothing to redo".to_string())
+            .await;
+    };
+    let mut applied = Vec::new();
+    for operation in step.operations {
+        if apply(rayshot_state, egui_ctx, &operation).await {
+            applied.push(operation);
+        }
+    }
+    crate::metadata::save(rayshot_state, egui_ctx).await;
+
+    if applied.is_empty() {
+        return;
+    }
+    let forgotten = update_journal(rayshot_state, egui_ctx, |journal| {
+        journal.push_undo(JournalStep {
+            operations: applied,
+        })
+    })
+    .await;
+    forget(rayshot_state, forgotten.flatten().into_iter().collect()).await;
+}
+
+// selects the entry the operation touches so the user sees what changed, errors are reported
+async fn apply(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    operation: &FileOperation,
+) -> bool {
+    match operation {
+        FileOperation::Move { filename, from } => {
+            let Some(entry) = select_entry(rayshot_state, filename, Some(*from)).await else {
+                crate::state::report_error(
+                    rayshot_state,
+                    egui_ctx,
+                    format!(
+                        "Cannot move '{}', it is no longer in the {}",
+                        filename,
+                        match from {
+                            crate::FileLocation::Local => "screenshot folder",
+                            crate::FileLocation::Trash => "trash",
+                        }
+                    ),
+                )
+                .await;
+                return false;
+            };
+            rayshot_state.manage_texture_cache(egui_ctx).await;
+            crate::storage::move_entry(rayshot_state, egui_ctx, &entry)
+                .await
+                .is_some()
+        }
+        FileOperation::Edit {
+            filename,
+            backup,
+            after,
+            ..
+        } => {
+            let Some(entry) = select_entry(rayshot_state, filename, None).await else {
+                crate::state::report_error(
+                    rayshot_state,
+                    egui_ctx,
+                    format!("Cannot revert the edit of '{}', it is gone", filename),
+                )
+                .await;
+                return false;
+            };
+            crate::storage::swap_entry_edit(rayshot_state, egui_ctx, &entry, backup, after).await
+        }
+        FileOperation::Rename { from, to } => {
+            let Some(entry) = select_entry(rayshot_state, from, None).await else {
+                crate::state::report_error(
+                    rayshot_state,
+                    egui_ctx,
+                    format!("Cannot rename '{}', it is gone", from),
+                )
+                .await;
+                return false;
+            };
+            crate::storage::rename_entry(rayshot_state, egui_ctx, &entry, to.clone())
+                .await
+                .is_some()
+        }
+    }
+}
+
+// makes the entry with `filename` in `location`, or in either, the current one
+async fn select_entry(
+    rayshot_state: &crate::RayshotState,
+    filename: &str,
+    location: Option<crate::FileLocation>,
+) -> Option<crate::ScreenshotEntry> {
+    let mut idx = rayshot_state.cur_screenshot_idx.lock().await;
+    let entries = rayshot_state.screenshot_entries.lock().await;
+    for (entry_idx, entry) in entries.iter().enumerate() {
+        if entry.filename.as_str() != filename {
+            continue;
+        }
+        if let Some(location) = location {
+            if *entry.file_location.lock().await != location {
+                continue;
+            }
+        }
+        *idx = entry_idx;
+        return Some(entry.clone());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    fn step(filename: &str) -> super::JournalStep {
+        super::JournalStep {
+            operations: vec![super::FileOperation::Move {
+                filename: filename.to_string(),
+                from: crate::FileLocation::Local,
+            }],
+        }
+    }
+
+    fn filenames(steps: &[super::JournalStep]) -> Vec<&str> {
+        steps
+            .iter()
+            .flat_map(|step| &step.operations)
+            .map(|operation| match operation {
+                super::FileOperation::Move { filename, .. }
+                | super::FileOperation::Edit { filename, .. } => filename.as_str(),
+                super::FileOperation::Rename { from, .. } => from.as_str(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn inverse_swaps_each_operation_and_undoes_itself() {
+        let annotation = crate::annotation::Annotation {
+            shape: crate::annotation::Shape::Callout {
+                at: [1.0, 2.0],
+                number: 1,
+            },
+            color: [255, 0, 0, 255],
+            size: 4.0,
+        };
+        let operations = [
+            super::FileOperation::Move {
+                filename: "a.png".to_string(),
+                from: crate::FileLocation::Local,
+            },
+            super::FileOperation::Edit {
+                filename: "a.png".to_string(),
+                backup: "a-backup.png".to_string(),
+                before: vec![annotation.clone()],
+                after: Vec::new(),
+            },
+            super::FileOperation::Rename {
+                from: "a.png".to_string(),
+                to: "b.png".to_string(),
+            },
+        ];
+        let inverses = [
+            super::FileOperation::Move {
+                filename: "a.png".to_string(),
+                from: crate::FileLocation::Trash,
+            },
+            super::FileOperation::Edit {
+                filename: "a.png".to_string(),
+                backup: "a-backup.png".to_string(),
+                before: Vec::new(),
+                after: vec![annotation],
+            },
+            super::FileOperation::Rename {
+                from: "b.png".to_string(),
+                to: "a.png".to_string(),
+            },
+        ];
+        for (operation, inverse) in operations.iter().zip(&inverses) {
+            assert_eq!(&operation.inverse(), inverse);
+            assert_eq!(&inverse.inverse(), operation);
+        }
+    }
+
+    #[test]
+    fn recording_a_step_forgets_the_undone_ones() {
+        let mut journal = super::Journal::default();
+        assert!(journal.record(step("a.png")).is_empty());
+        assert!(journal.record(step("b.png")).is_empty());
+        let undone = journal.undo.pop().unwrap();
+        journal.redo.push(undone);
+
+        let forgotten = journal.record(step("c.png"));
+        assert_eq!(filenames(&forgotten), ["b.png"]);
+        assert_eq!(filenames(&journal.undo), ["a.png", "c.png"]);
+        assert!(journal.redo.is_empty());
+    }
+
+    #[test]
+    fn the_oldest_steps_are_forgotten_past_the_limit() {
+        let mut journal = super::Journal::default();
+        for idx in 0..super::MAX_JOURNAL_STEPS {
+            assert!(journal.record(step(&format!("{}.png", idx))).is_empty());
+        }
+        let forgotten = journal.push_undo(step("last.png"));
+        assert_eq!(filenames(forgotten.as_slice()), ["0.png"]);
+        assert_eq!(journal.undo.len(), super::MAX_JOURNAL_STEPS);
+        assert_eq!(filenames(&journal.undo[..1]), ["1.png"]);
+    }
+
+    #[test]
+    fn updates_are_kept_on_disk() {
+        let root =
+            std::env::temp_dir().join(format!("rayshot-test-journal-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let config = crate::config::RayshotConfig {
+            screenshot_dir: root.clone(),
+            ..Default::default()
+        };
+        super::Journal::update(&config, |journal| journal.record(step("a.png"))).unwrap();
+        let (_, undone) = super::Journal::update(&config, |journal| journal.undo.pop()).unwrap();
+        super::Journal::update(&config, |journal| journal.redo.extend(undone)).unwrap();
+        let on_disk = super::Journal::load(&config).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(on_disk.undo.is_empty());
+        assert_eq!(filenames(&on_disk.redo), ["a.png"]);
+    }
+}
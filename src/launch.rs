@@ -0,0 +1,141 @@
+// hands screenshots to the desktop's file manager and to the configured external editor
+
+// shows `file` in the file manager, selected where the platform allows it, or `dir` when the
+// file cannot be found
+fn folder_command(
+    file: Option<&std::path::Path>,
+    dir: &std::path::Path,
+) -> tokio::process::Command {
+    if cfg!(target_os = "windows") {
+        let mut command = tokio::process::Command::new("explorer");
+        match file {
+            Some(file) => command.arg("/select,").arg(file),
+            None => command.arg(dir),
+        };
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = tokio::process::Command::new("open");
+        match file {
+            Some(file) => command.arg("-R").arg(file),
+            None => command.arg(dir),
+        };
+        command
+    } else {
+        // xdg-open has no way to select a file
+        let mut command = tokio::process::Command::new("xdg-open");
+        command.arg(file.and_then(std::path::Path::parent).unwrap_or(dir));
+        command
+    }
+}
+
+// `{path}` in the arguments is replaced by the path, which is appended when none has it
+fn editor_command(editor: &[String], path: &std::path::Path) -> Option<tokio::process::Command> {
+    let (program, args) = editor.split_first()?;
+    let mut command = tokio::process::Command::new(program);
+    let path = path.to_string_lossy();
+    for arg in args {
+        command.arg(arg.replace("{path}", &path));
+    }
+    if !args.iter().any(|arg| arg.contains("{path}")) {
+        command.arg(path.as_ref());
+    }
+    Some(command)
+}
+
+// the absolute paths of the entry's file, `None` when it cannot be found, and of its location
+async fn entry_path(
+    rayshot_state: &crate::RayshotState,
+    entry: &crate::ScreenshotEntry,
+) -> (Option<std::path::PathBuf>, std::path::PathBuf) {
+    let config = &rayshot_state.config;
+    let location = *entry.file_location.lock().await;
+    let file = location
+        .file_path(
+            config,
+            rayshot_state.freedesktop_trash.as_ref(),
+            entry.filename.as_str(),
+        )
+        .ok()
+        .filter(|path| path.is_file())
+        .and_then(|path| std::path::absolute(path).ok());
+    let dir = location.dir_path(config);
+    (
+        file,
+        std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf()),
+    )
+}
+
+// waits for the command so it is reaped, `what` names it in errors
+async fn run(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    mut command: tokio::process::Command,
+    what: &str,
+    check_status: bool,
+) {
+    let error_msg = match command.spawn() {
+        Ok(mut child) => match child.wait().await {
+            Ok(status) if status.success() || !check_status => return,
+            Ok(status) => format!("Failed to {}: the command exited with {}", what, status),
+            Err(e) => format!("Failed to {}: {}", what, e),
+        },
+        Err(e) => format!(
+            "Failed to {}: cannot run '{}': {}",
+            what,
+            command.as_std().get_program().to_string_lossy(),
+            e
+        ),
+    };
+    crate::state::report_error(rayshot_state, egui_ctx, error_msg).await;
+}
+
+pub async fn open_folder(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+) {
+    let (file, dir) = entry_path(rayshot_state, entry).await;
+    // explorer exits with 1 even when it worked
+    run(
+        rayshot_state,
+        egui_ctx,
+        folder_command(file.as_deref(), &dir),
+        "open the folder",
+        !cfg!(target_os = "windows"),
+    )
+    .await;
+}
+
+pub async fn open_in_editor(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+) {
+    let Some(editor) = &rayshot_state.config.external_editor else {
+        return crate::state::report_error(
+            rayshot_state,
+            egui_ctx,
+            "No external_editor is configured".to_string(),
+        )
+        .await;
+    };
+    let (Some(file), _) = entry_path(rayshot_state, entry).await else {
+        return crate::state::report_error(
+            rayshot_state,
+            egui_ctx,
+            format!("Cannot open '{}', the file is gone", entry.filename),
+        )
+        .await;
+    };
+    let Some(command) = editor_command(editor, &file) else {
+        return;
+    };
+    run(
+        rayshot_state,
+        egui_ctx,
+        command,
+        &format!("open '{}' in the external editor", entry.filename),
+        true,
+    )
+    .await;
+}
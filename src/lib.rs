@@ -0,0 +1,17 @@
+pub mod annotation;
+pub mod capture;
+pub mod clipboard;
+pub mod config;
+pub mod edit;
+pub mod filename;
+pub mod filter;
+pub mod journal;
+pub mod launch;
+pub mod metadata;
+pub mod redaction;
+pub mod state;
+pub mod storage;
+pub mod trash;
+pub mod ui;
+
+pub use state::{FileLocation, RayshotHotkey, RayshotState, ScreenshotEntry, ScreenshotState};
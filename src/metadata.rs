@@ -0,0 +1,726 @@
+const INDEX_FILE_NAME: &str = ".rayshot-index.toml";
+// changed records are appended here, one JSON object per line, instead of rewriting the index
+const INDEX_LOG_FILE_NAME: &str = ".rayshot-index.log";
+// the log is folded into the index once it holds this many changes
+const INDEX_LOG_COMPACT_LINES: usize = 1000;
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+pub const MAX_RATING: u8 = 5;
+
+// how often the GUI checks the index for changes the CLI made
+const INDEX_SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+// tags are shown and filtered as typed, only surrounding whitespace is dropped
+pub fn validate_tag(tag: &str) -> Result<(), String> {
+    if tag.trim().is_empty() {
+        return Err("tags must not be empty".to_string());
+    }
+    if tag.chars().any(char::is_control) {
+        return Err(format!("tag '{}' contains control characters", tag));
+    }
+    Ok(())
+}
+
+// what rayshot knows about a screenshot beyond its file, keyed by its filename
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScreenshotMetadata {
+    #[serde(with = "timestamp_format", skip_serializing_if = "Option::is_none")]
+    pub captured_at: Option<chrono::NaiveDateTime>,
+    // only known for screenshots rayshot captured itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<String>,
+    // filled in once the image is decoded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<u64>,
+    pub location: crate::FileLocation,
+    // when rayshot moved it to the trash, retention ages count from it
+    #[serde(with = "timestamp_format", skip_serializing_if = "Option::is_none")]
+    pub trashed_at: Option<chrono::NaiveDateTime>,
+    pub tags: Vec<String>,
+    // 0 is unrated, up to 5 stars
+    pub rating: u8,
+    pub notes: String,
+    // drawn over the screenshot, the file itself is never changed by them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<crate::annotation::Annotation>,
+    // frames captured by one burst share it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burst_id: Option<u64>,
+}
+
+impl Default for ScreenshotMetadata {
+    fn default() -> Self {
+        Self {
+            captured_at: None,
+            app_name: None,
+            window_title: None,
+            monitor: None,
+            width: None,
+            height: None,
+            hash: None,
+            file_size: None,
+            location: crate::FileLocation::Local,
+            trashed_at: None,
+            tags: Vec::new(),
+            rating: 0,
+            notes: String::new(),
+            annotations: Vec::new(),
+            burst_id: None,
+        }
+    }
+}
+
+impl ScreenshotMetadata {
+    // returns whether the tag is set afterwards
+    pub fn toggle_tag(&mut self, tag: &str) -> bool {
+        match self.tags.iter().position(|other| other == tag) {
+            Some(position) => {
+                self.tags.remove(position);
+                false
+            }
+            None => {
+                self.tags.push(tag.to_string());
+                true
+            }
+        }
+    }
+
+    // a move to the trash is timed, a restore forgets when it happened
+    pub fn set_location(&mut self, location: crate::FileLocation) {
+        self.location = location;
+        self.trashed_at = match location {
+            crate::FileLocation::Local => None,
+            crate::FileLocation::Trash => Some(chrono::Local::now().naive_local()),
+        };
+    }
+
+    // the pixel facts of a decoded image
+    pub fn set_image(&mut self, image_buffer: &image::RgbaImage) {
+        self.width = Some(image_buffer.width());
+        self.height = Some(image_buffer.height());
+        self.hash = Some(crate::filename::content_hash(image_buffer));
+    }
+}
+
+mod timestamp_format {
+    pub fn serialize<S: serde::Serializer>(
+        timestamp: &Option<chrono::NaiveDateTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match timestamp {
+            Some(timestamp) => {
+                serializer.serialize_str(&timestamp.format(super::TIMESTAMP_FORMAT).to_string())
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<chrono::NaiveDateTime>, D::Error> {
+        let timestamp: String = serde::Deserialize::deserialize(deserializer)?;
+        chrono::NaiveDateTime::parse_from_str(&timestamp, super::TIMESTAMP_FORMAT)
+            .map(Some)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// one line of the index log, `None` for a removed record
+#[derive(serde::Serialize, serde::Deserialize)]
+struct IndexLogLine {
+    filename: String,
+    record: Option<ScreenshotMetadata>,
+}
+
+// what the index and its log looked like on disk, the log only grows between compactions
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct IndexVersion {
+    index_modified: Option<std::time::SystemTime>,
+    log_modified: Option<std::time::SystemTime>,
+    log_len: u64,
+}
+
+// the sidecar index in the screenshot dir, a deleted index is rebuilt from the files by
+// `reconcile`, losing only what cannot be read back from them
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetadataIndex {
+    screenshots: std::collections::BTreeMap<String, ScreenshotMetadata>,
+    // the records changed since the last save, which writes them over the index on disk
+    #[serde(skip)]
+    changed: std::collections::BTreeSet<String>,
+    // records the CLI changed on disk, with what they were here before, until the entries follow
+    #[serde(skip)]
+    external_changes: Vec<(String, Option<ScreenshotMetadata>)>,
+    // the index and its log when they were last read or written here
+    #[serde(skip)]
+    version: IndexVersion,
+    // the complete lines of the log replayed by `load` and their length in bytes
+    #[serde(skip)]
+    log_lines: usize,
+    #[serde(skip)]
+    log_len: u64,
+}
+
+impl MetadataIndex {
+    pub fn path(config: &crate::config::RayshotConfig) -> std::path::PathBuf {
+        config.screenshot_dir.join(INDEX_FILE_NAME)
+    }
+
+    pub fn log_path(config: &crate::config::RayshotConfig) -> std::path::PathBuf {
+        config.screenshot_dir.join(INDEX_LOG_FILE_NAME)
+    }
+
+    fn version_on_disk(config: &crate::config::RayshotConfig) -> IndexVersion {
+        let index = std::fs::metadata(Self::path(config)).ok();
+        let log = std::fs::metadata(Self::log_path(config)).ok();
+        IndexVersion {
+            index_modified: index.and_then(|metadata| metadata.modified().ok()),
+            log_modified: log.as_ref().and_then(|metadata| metadata.modified().ok()),
+            log_len: log.map_or(0, |metadata| metadata.len()),
+        }
+    }
+
+    // the index with the changes of its log applied
+    pub fn load(config: &crate::config::RayshotConfig) -> std::io::Result<Self> {
+        let mut index = Self::read_index(config)?;
+        index.replay_log(config)?;
+        Ok(index)
+    }
+
+    fn read_index(config: &crate::config::RayshotConfig) -> std::io::Result<Self> {
+        let path = Self::path(config);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid metadata index '{}': {}", path.display(), e),
+                )
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    // a last line without its newline was cut short by a crash and never counted as written
+    fn replay_log(&mut self, config: &crate::config::RayshotConfig) -> std::io::Result<()> {
+        let path = Self::log_path(config);
+        let content = match std::fs::read(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let complete = content
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .map_or(0, |end| end + 1);
+        self.log_lines = 0;
+        for (line_idx, line) in content[..complete].split(|&byte| byte == b'\n').enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let IndexLogLine { filename, record } = serde_json::from_slice(line).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "Invalid metadata index log '{}' at line {}: {}",
+                        path.display(),
+                        line_idx + 1,
+                        e
+                    ),
+                )
+            })?;
+            match record {
+                Some(record) => self.screenshots.insert(filename, record),
+                None => self.screenshots.remove(&filename),
+            };
+            self.log_lines += 1;
+        }
+        self.log_len = complete as u64;
+        Ok(())
+    }
+
+    // writes the whole index and drops the log it takes in, a crash in between replays the log
+    // again, which only repeats records the index already has as long as the log was appended to
+    // last
+    pub fn save(&mut self, config: &crate::config::RayshotConfig) -> std::io::Result<()> {
+        let content = toml::to_string(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        crate::storage::write_atomically(&Self::path(config), content)?;
+        match std::fs::remove_file(Self::log_path(config)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        self.log_lines = 0;
+        self.log_len = 0;
+        Ok(())
+    }
+
+    // appends the records changed since the last write to the log, which is folded into the
+    // index once it is long
+    fn append_changes(&mut self, config: &crate::config::RayshotConfig) -> std::io::Result<()> {
+        let changed = std::mem::take(&mut self.changed);
+        if changed.is_empty() {
+            return Ok(());
+        }
+        let mut content = Vec::new();
+        for filename in &changed {
+            let line = IndexLogLine {
+                filename: filename.clone(),
+                record: self.screenshots.get(filename).cloned(),
+            };
+            serde_json::to_writer(&mut content, &line).map_err(std::io::Error::other)?;
+            content.push(b'\n');
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(Self::log_path(config))?;
+        // drops a line cut short by a crash, so the new ones start on a line of their own
+        file.set_len(self.log_len)?;
+        std::io::Seek::seek(&mut file, std::io::SeekFrom::Start(self.log_len))?;
+        std::io::Write::write_all(&mut file, &content)?;
+        file.sync_data()?;
+        self.log_lines += changed.len();
+        self.log_len += content.len() as u64;
+        if self.log_lines > INDEX_LOG_COMPACT_LINES {
+            self.save(config)?;
+        }
+        Ok(())
+    }
+
+    // reads the index, applies `update` and appends the records it changed, all under the shared
+    // lock so changes the GUI and the CLI make at the same time are all kept
+    pub fn update_file(
+        config: &crate::config::RayshotConfig,
+        update: impl FnOnce(&mut Self),
+    ) -> std::io::Result<Self> {
+        let _lock = crate::storage::lock_shared_files(config)?;
+        let mut index = Self::load(config)?;
+        update(&mut index);
+        index.append_changes(config)?;
+        index.version = Self::version_on_disk(config);
+        Ok(index)
+    }
+
+    // the records changed since the last save, `None` for removed ones, which count as saved
+    // from here on
+    fn take_changes(&mut self) -> Vec<(String, Option<ScreenshotMetadata>)> {
+        std::mem::take(&mut self.changed)
+            .into_iter()
+            .map(|filename| {
+                let record = self.screenshots.get(&filename).cloned();
+                (filename, record)
+            })
+            .collect()
+    }
+
+    // takes over the records the CLI changed in `saved`, except the ones changed here since
+    fn adopt(&mut self, saved: MetadataIndex) {
+        let mut screenshots = saved.screenshots;
+        let filenames: std::collections::BTreeSet<_> = self
+            .screenshots
+            .keys()
+            .chain(screenshots.keys())
+            .cloned()
+            .collect();
+        for filename in filenames {
+            let record = self.screenshots.remove(&filename);
+            if self.changed.contains(&filename) {
+                match record {
+                    Some(record) => screenshots.insert(filename, record),
+                    None => screenshots.remove(&filename),
+                };
+            } else if record.as_ref() != screenshots.get(&filename) {
+                self.external_changes.push((filename, record));
+            }
+        }
+        self.screenshots = screenshots;
+        self.version = saved.version;
+    }
+
+    // when the trashed screenshots were moved to the trash, for the ones it is known of
+    pub fn trash_times(&self) -> std::collections::HashMap<String, chrono::NaiveDateTime> {
+        self.screenshots
+            .iter()
+            .filter(|(_, metadata)| metadata.location == crate::FileLocation::Trash)
+            .filter_map(|(filename, metadata)| Some((filename.clone(), metadata.trashed_at?)))
+            .collect()
+    }
+
+    pub fn get(&self, filename: &str) -> Option<&ScreenshotMetadata> {
+        self.screenshots.get(filename)
+    }
+
+    pub fn get_mut(&mut self, filename: &str) -> Option<&mut ScreenshotMetadata> {
+        self.changed.insert(filename.to_string());
+        self.screenshots.get_mut(filename)
+    }
+
+    pub fn entry(&mut self, filename: &str) -> &mut ScreenshotMetadata {
+        self.changed.insert(filename.to_string());
+        self.screenshots.entry(filename.to_string()).or_default()
+    }
+
+    pub fn remove(&mut self, filename: &str) -> Option<ScreenshotMetadata> {
+        self.changed.insert(filename.to_string());
+        self.screenshots.remove(filename)
+    }
+
+    // brings the index in line with the scanned entries, dropping the records of files that
+    // are gone and adding what the file tells about the new ones
+    pub fn reconcile(
+        &mut self,
+        config: &crate::config::RayshotConfig,
+        trash: Option<&crate::trash::FreedesktopTrash>,
+        entries: &[crate::ScreenshotEntry],
+    ) {
+        let filenames: std::collections::HashSet<_> = entries
+            .iter()
+            .map(|entry| entry.filename.as_str())
+            .collect();
+        self.screenshots
+            .retain(|filename, _| filenames.contains(filename.as_str()));
+
+        for entry in entries {
+            let location = *entry.file_location.blocking_lock();
+            let is_new = !self.screenshots.contains_key(entry.filename.as_str());
+            let metadata = self.entry(&entry.filename);
+            // files moved in or out of the trash behind rayshot's back have no trash time
+            if metadata.location != location {
+                metadata.location = location;
+                metadata.trashed_at = None;
+            }
+            if let Some(file_size) = *entry.file_size.blocking_lock() {
+                metadata.file_size = Some(file_size as u64);
+            }
+            if !is_new {
+                continue;
+            }
+            let Ok(path) = location.file_path(config, trash, &entry.filename) else {
+                continue;
+            };
+            metadata.captured_at = crate::storage::parse_screenshot_timestamp(&entry.filename)
+                .or_else(|| {
+                    let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
+                    Some(chrono::DateTime::<chrono::Local>::from(modified).naive_local())
+                });
+            // only the header is read, the hash waits until the image is decoded for display
+            if let Ok((width, height)) = image::image_dimensions(&path) {
+                metadata.width = Some(width);
+                metadata.height = Some(height);
+            }
+        }
+    }
+}
+
+// moves a file that failed to load to `<name>.broken`, returns the error to report
+fn move_aside(path: &std::path::Path, e: std::io::Error, consequence: &str) -> String {
+    let mut broken_name = path.file_name().unwrap_or_default().to_os_string();
+    broken_name.push(".broken");
+    let broken_path = path.with_file_name(broken_name);
+    match std::fs::rename(path, &broken_path) {
+        Ok(()) => format!(
+            "{}, moved it to '{}' {}",
+            e,
+            broken_path.display(),
+            consequence
+        ),
+        Err(rename_error) => format!("{}, failed to move it aside: {}", e, rename_error),
+    }
+}
+
+// reads the index and reconciles it with the loaded entries, then writes it whole, a broken index
+// is moved aside to `.rayshot-index.toml.broken` and rebuilt, a broken log to
+// `.rayshot-index.log.broken`, returns the errors to report
+pub fn load_and_reconcile(
+    config: &crate::config::RayshotConfig,
+    trash: Option<&crate::trash::FreedesktopTrash>,
+    entries: &[crate::ScreenshotEntry],
+) -> (MetadataIndex, Vec<String>) {
+    let mut error_msgs = Vec::new();
+    let _lock = crate::storage::lock_shared_files(config)
+        .map_err(|e| error_msgs.push(format!("Failed to lock the metadata index: {}", e)));
+    let mut index = MetadataIndex::read_index(config).unwrap_or_else(|e| {
+        error_msgs.push(move_aside(
+            &MetadataIndex::path(config),
+            e,
+            "and rebuilt it from the screenshots",
+        ));
+        MetadataIndex::default()
+    });
+    // the changes before the broken line are kept, the ones after it are lost
+    if let Err(e) = index.replay_log(config) {
+        error_msgs.push(move_aside(
+            &MetadataIndex::log_path(config),
+            e,
+            "and kept the changes before the broken line",
+        ));
+    }
+    index.reconcile(config, trash, entries);
+    index.changed.clear();
+    if let Err(e) = index.save(config) {
+        error_msgs.push(format!("Failed to save the metadata index: {}", e));
+    }
+    index.version = MetadataIndex::version_on_disk(config);
+    (index, error_msgs)
+}
+
+// applies `change` to the index without writing it, a bulk action `save`s once at its end
+pub async fn change(rayshot_state: &crate::RayshotState, change: impl FnOnce(&mut MetadataIndex)) {
+    change(&mut *rayshot_state.metadata.lock().await);
+}
+
+// writes the records taken by `take_changes` over the index on disk and returns it
+fn write_changes(
+    config: &crate::config::RayshotConfig,
+    changes: &[(String, Option<ScreenshotMetadata>)],
+) -> std::io::Result<MetadataIndex> {
+    MetadataIndex::update_file(config, |index| {
+        for (filename, record) in changes {
+            match record {
+                Some(record) => *index.entry(filename) = record.clone(),
+                None => {
+                    index.remove(filename);
+                }
+            }
+        }
+    })
+}
+
+// writes the changed records over a freshly read index and takes over what the CLI changed in it
+// meanwhile, the file is only read when it changed since this process last saw it
+fn sync_blocking(rayshot_state: &crate::RayshotState) -> std::io::Result<()> {
+    let config = &rayshot_state.config;
+    let changes = {
+        let mut metadata = rayshot_state.metadata.blocking_lock();
+        if metadata.changed.is_empty() && metadata.version == MetadataIndex::version_on_disk(config)
+        {
+            return Ok(());
+        }
+        metadata.take_changes()
+    };
+    let saved = write_changes(config, &changes);
+    let mut metadata = rayshot_state.metadata.blocking_lock();
+    match saved {
+        Ok(saved) => {
+            metadata.adopt(saved);
+            Ok(())
+        }
+        Err(e) => {
+            metadata
+                .changed
+                .extend(changes.into_iter().map(|(filename, _)| filename));
+            Err(e)
+        }
+    }
+}
+
+// writes the changed records without holding the index lock, so the UI can still read it, and
+// brings the entries in line with what the CLI changed, writes wait for each other
+pub async fn save(rayshot_state: &crate::RayshotState, egui_ctx: &eframe::egui::Context) {
+    let _writer = rayshot_state.metadata_writer.lock().await;
+    let result = {
+        let rayshot_state = rayshot_state.clone();
+        tokio::task::spawn_blocking(move || sync_blocking(&rayshot_state))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+    };
+    if let Err(e) = result {
+        crate::state::report_error(
+            rayshot_state,
+            egui_ctx,
+            format!("Failed to save the metadata index: {}", e),
+        )
+        .await;
+    }
+    let external_changes =
+        std::mem::take(&mut rayshot_state.metadata.lock().await.external_changes);
+    if !external_changes.is_empty() {
+        rayshot_state
+            .follow_external_changes(egui_ctx, external_changes)
+            .await;
+    }
+}
+
+// notices what the CLI changed while the GUI runs
+pub async fn run_index_sync(rayshot_state: crate::RayshotState, egui_ctx: eframe::egui::Context) {
+    let mut interval = tokio::time::interval(INDEX_SYNC_INTERVAL);
+    loop {
+        interval.tick().await;
+        save(&rayshot_state, &egui_ctx).await;
+    }
+}
+
+// applies `update` to the index and saves it, reporting errors
+pub async fn update(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    update: impl FnOnce(&mut MetadataIndex),
+) {
+    change(rayshot_state, update).await;
+    save(rayshot_state, egui_ctx).await;
+}
+
+// `update` for blocking tasks, the entries follow the CLI's changes on the next `save`
+pub fn update_blocking(
+    rayshot_state: &crate::RayshotState,
+    update: impl FnOnce(&mut MetadataIndex),
+) {
+    update(&mut rayshot_state.metadata.blocking_lock());
+    let _writer = rayshot_state.metadata_writer.blocking_lock();
+    if let Err(e) = sync_blocking(rayshot_state) {
+        let err_str = format!("Failed to save the metadata index: {}", e);
+        eprintln!("{}", err_str);
+        rayshot_state.error_messages.blocking_lock().push(err_str);
+    }
+}
+
+// the target of the rating and tag hotkeys
+async fn current_filename(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+) -> Option<std::sync::Arc<String>> {
+    match rayshot_state.get_current_screenshot().await {
+        Some(entry) => Some(entry.filename),
+        None => {
+            crate::state::report_error(
+                rayshot_state,
+                egui_ctx,
+                "No current screenshot to rate or tag".to_string(),
+            )
+            .await;
+            None
+        }
+    }
+}
+
+pub async fn rate_current(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    rating: u8,
+) {
+    let Some(filename) = current_filename(rayshot_state, egui_ctx).await else {
+        return;
+    };
+    update(rayshot_state, egui_ctx, |metadata| {
+        metadata.entry(&filename).rating = rating.min(MAX_RATING);
+    })
+    .await;
+    egui_ctx.request_repaint();
+}
+
+pub async fn toggle_current_tag(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    tag: &str,
+) {
+    let Some(filename) = current_filename(rayshot_state, egui_ctx).await else {
+        return;
+    };
+    update(rayshot_state, egui_ctx, |metadata| {
+        metadata.entry(&filename).toggle_tag(tag.trim());
+    })
+    .await;
+    egui_ctx.request_repaint();
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn saving_keeps_the_records_the_cli_changed_meanwhile() {
+        let root = std::env::temp_dir().join(format!("rayshot-test-index-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let config = crate::config::RayshotConfig {
+            screenshot_dir: root.clone(),
+            ..Default::default()
+        };
+        let mut gui = super::MetadataIndex::default();
+        gui.entry("gui.png").rating = 1;
+        gui.entry("both.png").rating = 1;
+        let changes = gui.take_changes();
+        gui.entry("both.png").rating = 2;
+
+        super::MetadataIndex::update_file(&config, |cli| {
+            cli.entry("cli.png").rating = 3;
+            cli.entry("both.png").rating = 3;
+        })
+        .unwrap();
+        let saved = super::write_changes(&config, &changes).unwrap();
+        gui.adopt(saved);
+        let on_disk = super::MetadataIndex::load(&config).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let rating = |index: &super::MetadataIndex, filename| index.get(filename).unwrap().rating;
+        assert_eq!(rating(&on_disk, "gui.png"), 1);
+        assert_eq!(rating(&on_disk, "cli.png"), 3);
+        assert_eq!(rating(&on_disk, "both.png"), 1);
+        assert_eq!(rating(&gui, "cli.png"), 3);
+        // changed again here after the save took the changes, written by the next save
+        assert_eq!(rating(&gui, "both.png"), 2);
+        assert_eq!(gui.external_changes, [("cli.png".to_string(), None)]);
+    }
+
+    #[test]
+    fn changes_are_appended_to_the_log_until_it_is_folded_into_the_index() {
+        let root = std::env::temp_dir().join(format!("rayshot-test-log-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let config = crate::config::RayshotConfig {
+            screenshot_dir: root.clone(),
+            ..Default::default()
+        };
+        let set_rating = |filename: &str, rating| {
+            super::MetadataIndex::update_file(&config, |index| {
+                index.entry(filename).rating = rating;
+            })
+            .unwrap()
+        };
+        set_rating("a.png", 1);
+        set_rating("b.png", 2);
+        set_rating("a.png", 3);
+        let log_path = super::MetadataIndex::log_path(&config);
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        assert_eq!(log.lines().count(), 3);
+        assert!(!super::MetadataIndex::path(&config).exists());
+
+        // a line cut short by a crash is neither replayed nor kept in front of the next one
+        std::fs::write(&log_path, format!("{}{{\"filename\":\"b.p", log)).unwrap();
+        let index = set_rating("c.png", 4);
+        assert_eq!(index.get("a.png").unwrap().rating, 3);
+        assert_eq!(index.get("b.png").unwrap().rating, 2);
+        assert_eq!(
+            std::fs::read_to_string(&log_path).unwrap().lines().count(),
+            4
+        );
+
+        for rating in 0..super::INDEX_LOG_COMPACT_LINES {
+            set_rating("d.png", rating as u8 % 5);
+        }
+        let folded = std::fs::read_to_string(super::MetadataIndex::path(&config)).unwrap();
+        let log_lines = std::fs::read_to_string(&log_path).map_or(0, |log| log.lines().count());
+        let on_disk = super::MetadataIndex::load(&config).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert!(folded.contains("a.png"));
+        assert!(log_lines < super::INDEX_LOG_COMPACT_LINES);
+        assert_eq!(on_disk.get("c.png").unwrap().rating, 4);
+        assert_eq!(
+            on_disk.get("d.png").unwrap().rating,
+            (super::INDEX_LOG_COMPACT_LINES - 1) as u8 % 5
+        );
+    }
+}
@@ -0,0 +1,184 @@
+// masks burnt into every capture of a profile before it is saved, for account names and ids
+// shown at fixed places of a game window
+
+const DEFAULT_PIXELATE_BLOCK: f32 = 16.0;
+const DEFAULT_BLUR_SIGMA: f32 = 12.0;
+// larger blocks or sigmas change nothing more on any real frame, and keep block arithmetic in u32
+const MAX_STRENGTH: f32 = 1024.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactionStyle {
+    // the only style that leaves nothing of the original pixels
+    #[default]
+    Fill,
+    Pixelate,
+    Blur,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedactionRule {
+    // fractions of the captured frame's size from its top left, so rules follow window resizes
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub style: RedactionStyle,
+    // the fill color, black when unset
+    #[serde(default)]
+    pub color: [u8; 3],
+    // the pixelate block size or the blur sigma in pixels
+    #[serde(default)]
+    pub strength: Option<f32>,
+}
+
+impl RedactionRule {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [("x", self.x), ("y", self.y)] {
+            if !(0.0..1.0).contains(&value) {
+                return Err(format!("'{}' must be at least 0 and less than 1", name));
+            }
+        }
+        for (name, value, start) in [
+            ("width", self.width, self.x),
+            ("height", self.height, self.y),
+        ] {
+            if !(value > 0.0 && start + value <= 1.0) {
+                return Err(format!(
+                    "'{}' must be more than 0 and keep the rule inside the frame",
+                    name
+                ));
+            }
+        }
+        match (self.style, self.strength) {
+            (RedactionStyle::Fill, Some(_)) => {
+                Err("'strength' only applies to pixelate and blur".to_string())
+            }
+            (_, Some(strength)) if !(1.0..=MAX_STRENGTH).contains(&strength) => Err(format!(
+                "'strength' must be at least 1 and at most {}",
+                MAX_STRENGTH
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // the opposite corners of the rule in the pixels of a `width`×`height` frame
+    fn corners(&self, width: u32, height: u32) -> ([f32; 2], [f32; 2]) {
+        let (width, height) = (width as f32, height as f32);
+        (
+            [self.x * width, self.y * height],
+            [
+                (self.x + self.width) * width,
+                (self.y + self.height) * height,
+            ],
+        )
+    }
+}
+
+pub fn apply(image_buffer: &mut image::RgbaImage, rules: &[RedactionRule]) {
+    for rule in rules {
+        let (from, to) = rule.corners(image_buffer.width(), image_buffer.height());
+        match rule.style {
+            RedactionStyle::Fill => {
+                let [r, g, b] = rule.color;
+                crate::annotation::fill(image_buffer, from, to, [r, g, b, 255]);
+            }
+            RedactionStyle::Pixelate => crate::annotation::pixelate(
+                image_buffer,
+                from,
+                to,
+                rule.strength.unwrap_or(DEFAULT_PIXELATE_BLOCK),
+            ),
+            RedactionStyle::Blur => crate::annotation::blur(
+                image_buffer,
+                from,
+                to,
+                rule.strength.unwrap_or(DEFAULT_BLUR_SIGMA),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn rule(style: super::RedactionStyle, strength: Option<f32>) -> super::RedactionRule {
+        super::RedactionRule {
+            x: 0.25,
+            y: 0.5,
+            width: 0.75,
+            height: 0.5,
+            style,
+            color: [0, 0, 0],
+            strength,
+        }
+    }
+
+    #[test]
+    fn validate_keeps_rules_inside_the_frame() {
+        assert!(rule(super::RedactionStyle::Fill, None).validate().is_ok());
+        let outside = [
+            (-0.1, 0.5, 0.5, 0.5),
+            (1.0, 0.5, 0.1, 0.1),
+            (0.5, 0.5, 0.0, 0.5),
+            (0.5, 0.5, 0.6, 0.5),
+            (0.5, 0.5, 0.5, 0.6),
+            (f32::NAN, 0.5, 0.5, 0.5),
+            (0.5, 0.5, f32::NAN, 0.5),
+        ];
+        for (x, y, width, height) in outside {
+            let rule = super::RedactionRule {
+                x,
+                y,
+                width,
+                height,
+                ..rule(super::RedactionStyle::Fill, None)
+            };
+            assert!(rule.validate().is_err(), "{:?}", rule);
+        }
+    }
+
+    #[test]
+    fn validate_bounds_the_strength_of_pixelate_and_blur() {
+        for (style, strength, valid) in [
+            (super::RedactionStyle::Pixelate, None, true),
+            (super::RedactionStyle::Pixelate, Some(1.0), true),
+            (super::RedactionStyle::Blur, Some(super::MAX_STRENGTH), true),
+            (super::RedactionStyle::Pixelate, Some(0.5), false),
+            (
+                super::RedactionStyle::Blur,
+                Some(super::MAX_STRENGTH + 1.0),
+                false,
+            ),
+            (super::RedactionStyle::Pixelate, Some(f32::INFINITY), false),
+            (super::RedactionStyle::Pixelate, Some(f32::NAN), false),
+            (super::RedactionStyle::Fill, Some(4.0), false),
+        ] {
+            assert_eq!(
+                rule(style, strength).validate().is_ok(),
+                valid,
+                "{:?} {:?}",
+                style,
+                strength
+            );
+        }
+    }
+
+    #[test]
+    fn the_largest_pixelate_block_covers_the_rule_in_one_color() {
+        let mut image_buffer = image::RgbaImage::from_fn(8, 8, |x, y| {
+            image::Rgba([(x * 30) as u8, (y * 30) as u8, 0, 255])
+        });
+        super::apply(
+            &mut image_buffer,
+            &[rule(
+                super::RedactionStyle::Pixelate,
+                Some(super::MAX_STRENGTH),
+            )],
+        );
+        let covered = *image_buffer.get_pixel(2, 4);
+        assert!((2..8).all(|x| (4..8).all(|y| *image_buffer.get_pixel(x, y) == covered)));
+        assert_ne!(*image_buffer.get_pixel(0, 0), covered);
+    }
+}
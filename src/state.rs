@@ -0,0 +1,617 @@
+// how long the texture cache waits for entries busy with their file before it looks again
+const TEXTURE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileLocation {
+    Local,
+    Trash,
+}
+
+impl FileLocation {
+    pub fn dir_path<'a>(&self, config: &'a crate::config::RayshotConfig) -> &'a std::path::Path {
+        match self {
+            FileLocation::Local => &config.screenshot_dir,
+            FileLocation::Trash => &config.trash_dir,
+        }
+    }
+
+    // the freedesktop trash renames files on collisions, so they are looked up by where they
+    // were trashed from, through `trash` when the caller keeps one for the session
+    pub fn file_path(
+        &self,
+        config: &crate::config::RayshotConfig,
+        trash: Option<&crate::trash::FreedesktopTrash>,
+        filename: &str,
+    ) -> std::io::Result<std::path::PathBuf> {
+        match (self, config.trash_backend) {
+            (FileLocation::Trash, crate::config::TrashBackend::Freedesktop) => {
+                let original_path = config.screenshot_dir.join(filename);
+                let trash = crate::trash::FreedesktopTrash::shared_or_home(trash)?;
+                match trash.find(&original_path)? {
+                    Some(item) => Ok(trash.file_path(&item)),
+                    None => Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("'{}' is not in the trash", original_path.display()),
+                    )),
+                }
+            }
+            _ => Ok(self.dir_path(config).join(filename)),
+        }
+    }
+}
+
+pub struct ScreenshotState {
+    pub capturing: bool,
+    pub writing: bool,
+    pub moving: bool,
+    pub loading: bool,
+    pub failed: bool,
+}
+
+impl ScreenshotState {
+    pub fn new() -> Self {
+        Self {
+            capturing: false,
+            writing: false,
+            moving: false,
+            loading: false,
+            failed: false,
+        }
+    }
+}
+
+impl Default for ScreenshotState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone)]
+pub struct ScreenshotEntry {
+    pub state: std::sync::Arc<tokio::sync::Mutex<ScreenshotState>>,
+    pub texture_handle: std::sync::Arc<tokio::sync::Mutex<Option<eframe::epaint::TextureHandle>>>,
+    pub demension: std::sync::Arc<tokio::sync::Mutex<Option<(usize, usize)>>>,
+    pub filename: std::sync::Arc<String>,
+    pub file_location: std::sync::Arc<tokio::sync::Mutex<FileLocation>>,
+    pub file_size: std::sync::Arc<tokio::sync::Mutex<Option<usize>>>,
+    pub file_lock: std::sync::Arc<tokio::sync::Mutex<()>>,
+    // frames of the same burst share an id, persisted in the metadata index
+    pub burst_id: Option<u64>,
+}
+
+impl ScreenshotEntry {
+    pub fn new(filename: std::sync::Arc<String>, file_location: FileLocation) -> Self {
+        Self {
+            texture_handle: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+            demension: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+            filename,
+            file_location: std::sync::Arc::new(tokio::sync::Mutex::new(file_location)),
+            file_size: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+            file_lock: std::sync::Arc::new(tokio::sync::Mutex::new(())),
+            state: std::sync::Arc::new(tokio::sync::Mutex::new(ScreenshotState::new())),
+            burst_id: None,
+        }
+    }
+}
+
+// shown by the UI while a bulk action runs over the selected entries
+#[derive(Debug, Clone)]
+pub struct BulkProgress {
+    pub label: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+// a captured frame waiting for the UI overlay to pick the part to keep
+pub struct RegionSelectionRequest {
+    pub image: std::sync::Arc<image::RgbaImage>,
+    // `(x, y, width, height)` in image pixels, `None` when the capture is cancelled
+    pub reply: tokio::sync::oneshot::Sender<Option<(u32, u32, u32, u32)>>,
+}
+
+#[derive(Clone)]
+pub struct RayshotState {
+    pub config: std::sync::Arc<crate::config::RayshotConfig>,
+    pub capture_source: std::sync::Arc<dyn crate::capture::CaptureSource>,
+    pub screenshot_entries: std::sync::Arc<tokio::sync::Mutex<Vec<ScreenshotEntry>>>,
+    pub cur_screenshot_idx: std::sync::Arc<tokio::sync::Mutex<usize>>,
+    // the profile the CaptureScreenshot hotkey captures, indexes `config.profiles`
+    pub active_profile_idx: std::sync::Arc<tokio::sync::Mutex<usize>>,
+    // taken by the UI, which replies once the user is done with the overlay
+    pub region_selection: std::sync::Arc<tokio::sync::Mutex<Option<RegionSelectionRequest>>>,
+    // kept up to date by the hotkey thread, bursts with `while_held` poll it
+    pub held_hotkeys: std::sync::Arc<tokio::sync::Mutex<std::collections::HashSet<RayshotHotkey>>>,
+    // indexes of the profiles with a burst in progress
+    pub running_bursts: std::sync::Arc<tokio::sync::Mutex<std::collections::HashSet<usize>>>,
+    pub next_burst_id: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    // notified when the texture cache skipped entries busy with their file, see `run_texture_retry`
+    pub texture_retry: std::sync::Arc<tokio::sync::Notify>,
+    // file moves that Undo and Redo can revert, as last read from or written to the screenshot dir
+    pub journal: std::sync::Arc<tokio::sync::Mutex<crate::journal::Journal>>,
+    pub bulk_progress: std::sync::Arc<tokio::sync::Mutex<Option<BulkProgress>>>,
+    // the sidecar index, kept in sync with every capture and move
+    pub metadata: std::sync::Arc<tokio::sync::Mutex<crate::metadata::MetadataIndex>>,
+    // held while the index is written, so writes happen one at a time
+    pub metadata_writer: std::sync::Arc<tokio::sync::Mutex<()>>,
+    // the desktop trash when it is the trash backend, remembers where trashed screenshots went
+    pub freedesktop_trash: Option<crate::trash::FreedesktopTrash>,
+    // set by the UI's filter bar
+    pub gallery_filter: std::sync::Arc<tokio::sync::Mutex<crate::filter::GalleryFilter>>,
+    // opened on the first copy and kept open so X11 and Wayland can still paste from it
+    pub clipboard: std::sync::Arc<tokio::sync::Mutex<Option<arboard::Clipboard>>>,
+    pub error_messages: std::sync::Arc<tokio::sync::Mutex<Vec<String>>>,
+}
+
+impl RayshotState {
+    pub fn new(
+        config: crate::config::RayshotConfig,
+        capture_source: std::sync::Arc<dyn crate::capture::CaptureSource>,
+    ) -> Self {
+        let freedesktop_trash = match config.trash_backend {
+            crate::config::TrashBackend::Freedesktop => crate::trash::FreedesktopTrash::home().ok(),
+            crate::config::TrashBackend::Folder => None,
+        };
+        Self {
+            config: std::sync::Arc::new(config),
+            capture_source,
+            screenshot_entries: std::sync::Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            cur_screenshot_idx: std::sync::Arc::new(tokio::sync::Mutex::new(0)),
+            active_profile_idx: std::sync::Arc::new(tokio::sync::Mutex::new(0)),
+            region_selection: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+            held_hotkeys: std::sync::Arc::new(tokio::sync::Mutex::new(Default::default())),
+            running_bursts: std::sync::Arc::new(tokio::sync::Mutex::new(Default::default())),
+            next_burst_id: std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0)),
+            texture_retry: Default::default(),
+            journal: std::sync::Arc::new(tokio::sync::Mutex::new(Default::default())),
+            bulk_progress: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+            metadata: std::sync::Arc::new(tokio::sync::Mutex::new(Default::default())),
+            metadata_writer: std::sync::Arc::new(tokio::sync::Mutex::new(())),
+            freedesktop_trash,
+            gallery_filter: std::sync::Arc::new(tokio::sync::Mutex::new(Default::default())),
+            clipboard: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+            error_messages: std::sync::Arc::new(tokio::sync::Mutex::new(Vec::new())),
+        }
+    }
+
+    // the next entry the gallery filter shows, staying put at the last one
+    pub async fn try_increment_screenshot_index(&self) -> usize {
+        let filter = self.gallery_filter.lock().await.clone();
+        let mut idx = self.cur_screenshot_idx.lock().await;
+        let entries = self.screenshot_entries.lock().await;
+        let metadata = self.metadata.lock().await;
+        *idx = (*idx).min(entries.len().saturating_sub(1));
+        for next_idx in *idx + 1..entries.len() {
+            if filter.matches_entry(&entries[next_idx], &metadata).await {
+                *idx = next_idx;
+                break;
+            }
+        }
+        *idx
+    }
+
+    // the previous entry the gallery filter shows, staying put at the first one
+    pub async fn try_decrement_screenshot_index(&self) -> usize {
+        let filter = self.gallery_filter.lock().await.clone();
+        let mut idx = self.cur_screenshot_idx.lock().await;
+        let entries = self.screenshot_entries.lock().await;
+        let metadata = self.metadata.lock().await;
+        for prev_idx in (0..(*idx).min(entries.len())).rev() {
+            if filter.matches_entry(&entries[prev_idx], &metadata).await {
+                *idx = prev_idx;
+                break;
+            }
+        }
+        *idx
+    }
+
+    // moves off a current entry the new filter hides, to the next shown one or else the
+    // previous one
+    pub async fn set_gallery_filter(&self, filter: crate::filter::GalleryFilter) {
+        *self.gallery_filter.lock().await = filter.clone();
+        let mut idx = self.cur_screenshot_idx.lock().await;
+        let entries = self.screenshot_entries.lock().await;
+        let metadata = self.metadata.lock().await;
+        let Some(current) = entries.get(*idx) else {
+            return;
+        };
+        if filter.matches_entry(current, &metadata).await {
+            return;
+        }
+        let candidates = (*idx + 1..entries.len()).chain((0..*idx).rev());
+        for candidate in candidates {
+            if filter.matches_entry(&entries[candidate], &metadata).await {
+                *idx = candidate;
+                return;
+            }
+        }
+    }
+
+    pub async fn select_screenshot_index(&self, index: usize) -> usize {
+        let mut idx = self.cur_screenshot_idx.lock().await;
+        let entries = self.screenshot_entries.lock().await;
+        *idx = index.min(entries.len().saturating_sub(1));
+        *idx
+    }
+
+    pub async fn get_current_screenshot(&self) -> Option<ScreenshotEntry> {
+        let idx = *self.cur_screenshot_idx.lock().await;
+        let entries = self.screenshot_entries.lock().await;
+        entries.get(idx).cloned()
+    }
+
+    pub async fn load_existing_screenshots(&self) -> std::io::Result<()> {
+        let config = self.config.clone();
+        let mut scanned = tokio::task::spawn_blocking(move || -> std::io::Result<_> {
+            let mut scanned = crate::storage::scan_screenshot_dir(&config, FileLocation::Local)?;
+            scanned.extend(crate::storage::scan_screenshot_dir(
+                &config,
+                FileLocation::Trash,
+            )?);
+            Ok(scanned)
+        })
+        .await??;
+        scanned.sort_by_key(|(timestamp, _)| *timestamp);
+
+        let mut entries = self.screenshot_entries.lock().await;
+        entries.extend(scanned.into_iter().map(|(_, entry)| entry));
+        *self.cur_screenshot_idx.lock().await = entries.len().saturating_sub(1);
+        Ok(())
+    }
+
+    // call after `load_existing_screenshots`, returns the errors to report
+    pub async fn load_metadata(&self) -> Vec<String> {
+        let config = self.config.clone();
+        let trash = self.freedesktop_trash.clone();
+        let entries = self.screenshot_entries.lock().await.clone();
+        match tokio::task::spawn_blocking(move || {
+            crate::metadata::load_and_reconcile(&config, trash.as_ref(), &entries)
+        })
+        .await
+        {
+            Ok((metadata, error_msgs)) => {
+                // keep bursts grouped across restarts, new bursts get ids past the stored ones
+                let mut entries = self.screenshot_entries.lock().await;
+                for entry in entries.iter_mut() {
+                    entry.burst_id = metadata
+                        .get(entry.filename.as_str())
+                        .and_then(|record| record.burst_id);
+                }
+                if let Some(last_burst_id) = entries.iter().filter_map(|entry| entry.burst_id).max()
+                {
+                    self.next_burst_id
+                        .fetch_max(last_burst_id + 1, std::sync::atomic::Ordering::SeqCst);
+                }
+                *self.metadata.lock().await = metadata;
+                error_msgs
+            }
+            Err(e) => vec![format!("Metadata index task failed: {}", e)],
+        }
+    }
+
+    pub async fn load_journal(&self) -> std::io::Result<()> {
+        let config = self.config.clone();
+        let journal =
+            tokio::task::spawn_blocking(move || crate::journal::Journal::load(&config)).await??;
+        *self.journal.lock().await = journal;
+        Ok(())
+    }
+
+    // inserts `entry` right after `existing`, or at the end when `existing` is gone, keeping the
+    // current entry selected
+    pub async fn insert_entry_after(&self, existing: &ScreenshotEntry, entry: ScreenshotEntry) {
+        let mut idx = self.cur_screenshot_idx.lock().await;
+        let mut entries = self.screenshot_entries.lock().await;
+        let position = entries
+            .iter()
+            .position(|other| std::sync::Arc::ptr_eq(&other.filename, &existing.filename))
+            .map_or(entries.len(), |position| position + 1);
+        if position <= *idx && !entries.is_empty() {
+            *idx += 1;
+        }
+        entries.insert(position, entry);
+    }
+
+    // drops the entries of deleted files in `location`, or in either, keeping the current entry
+    // selected when it stays
+    pub async fn remove_entries(
+        &self,
+        filenames: &std::collections::HashSet<String>,
+        location: Option<FileLocation>,
+    ) {
+        let mut idx = self.cur_screenshot_idx.lock().await;
+        let mut entries = self.screenshot_entries.lock().await;
+        let mut kept_idx = 0;
+        let mut new_idx = None;
+        let mut kept = Vec::with_capacity(entries.len());
+        for (entry_idx, entry) in entries.drain(..).enumerate() {
+            let in_location = match location {
+                Some(location) => *entry.file_location.lock().await == location,
+                None => true,
+            };
+            if in_location && filenames.contains(entry.filename.as_str()) {
+                continue;
+            }
+            if entry_idx <= *idx {
+                new_idx = Some(kept_idx);
+            }
+            kept_idx += 1;
+            kept.push(entry);
+        }
+        *entries = kept;
+        *idx = new_idx.unwrap_or(0);
+    }
+
+    // brings the entries in line with the index records the CLI changed, screenshots it
+    // captured are added, moved and edited ones follow and purged ones are dropped
+    pub async fn follow_external_changes(
+        &self,
+        egui_ctx: &eframe::egui::Context,
+        changes: Vec<(String, Option<crate::metadata::ScreenshotMetadata>)>,
+    ) {
+        let mut forgotten = Vec::new();
+        for (filename, previous) in changes {
+            let record = self.metadata.lock().await.get(&filename).cloned();
+            let entry = self
+                .screenshot_entries
+                .lock()
+                .await
+                .iter()
+                .find(|entry| entry.filename.as_str() == filename)
+                .cloned();
+            match (entry, record) {
+                (Some(entry), Some(record)) => {
+                    let _file_lock = entry.file_lock.lock().await;
+                    *entry.file_location.lock().await = record.location;
+                    *entry.file_size.lock().await = record.file_size.map(|size| size as usize);
+                    // an edit changed the pixels, the texture cache loads them again
+                    if previous.is_some_and(|previous| previous.hash != record.hash) {
+                        *entry.texture_handle.lock().await = None;
+                    }
+                }
+                (None, Some(record)) => {
+                    let mut entry =
+                        ScreenshotEntry::new(std::sync::Arc::new(filename), record.location);
+                    *entry.file_size.lock().await = record.file_size.map(|size| size as usize);
+                    entry.burst_id = record.burst_id;
+                    self.screenshot_entries.lock().await.push(entry);
+                }
+                (Some(entry), None) => forgotten.push(entry),
+                (None, None) => {}
+            }
+        }
+        // a record can also be gone because the index was deleted, only missing files are dropped
+        let config = self.config.clone();
+        let trash = self.freedesktop_trash.clone();
+        let mut located = Vec::with_capacity(forgotten.len());
+        for entry in forgotten {
+            let location = *entry.file_location.lock().await;
+            located.push((entry.filename.to_string(), location));
+        }
+        let purged = tokio::task::spawn_blocking(move || {
+            located
+                .into_iter()
+                .filter(|(filename, location)| {
+                    !location
+                        .file_path(&config, trash.as_ref(), filename)
+                        .is_ok_and(|path| path.is_file())
+                })
+                .map(|(filename, _)| filename)
+                .collect::<std::collections::HashSet<_>>()
+        })
+        .await
+        .unwrap_or_default();
+        if !purged.is_empty() {
+            self.remove_entries(&purged, None).await;
+        }
+        self.manage_texture_cache(egui_ctx).await;
+        egui_ctx.request_repaint();
+    }
+
+    // keep textures loaded only for a window of entries around the current index,
+    // loading the missing ones from disk in the background, entries busy with their file are
+    // loaded by a later pass
+    pub async fn manage_texture_cache(&self, egui_ctx: &eframe::egui::Context) {
+        let max_loaded_textures = self.config.max_loaded_textures;
+        let idx = *self.cur_screenshot_idx.lock().await;
+        let entries = self.screenshot_entries.lock().await;
+        let window_end = (idx + max_loaded_textures / 2 + 1)
+            .max(max_loaded_textures)
+            .min(entries.len());
+        let window_start = window_end.saturating_sub(max_loaded_textures);
+
+        let mut any_busy = false;
+        for (entry_idx, entry) in entries.iter().enumerate() {
+            if entry_idx < window_start || entry_idx >= window_end {
+                if let Ok(mut texture_lock) = entry.texture_handle.try_lock() {
+                    *texture_lock = None; // the TextureHandle drop will handle the freeing
+                }
+                continue;
+            }
+
+            if entry.texture_handle.lock().await.is_some() {
+                continue;
+            }
+            {
+                let mut state = entry.state.lock().await;
+                if state.capturing || state.writing || state.moving || state.loading {
+                    any_busy = true;
+                    continue;
+                }
+                if state.failed {
+                    continue;
+                }
+                state.loading = true;
+            }
+
+            let entry = entry.clone();
+            let rayshot_state = self.clone();
+            let egui_ctx = egui_ctx.clone();
+            tokio::task::spawn_blocking(move || {
+                {
+                    let _file_lock = entry.file_lock.blocking_lock();
+                    let file_path = entry.file_location.blocking_lock().file_path(
+                        &rayshot_state.config,
+                        rayshot_state.freedesktop_trash.as_ref(),
+                        entry.filename.as_str(),
+                    );
+                    match file_path
+                        .map_err(image::ImageError::IoError)
+                        .and_then(image::open)
+                    {
+                        Ok(image) => {
+                            let image_buffer = image.to_rgba8();
+                            let needs_image_facts = rayshot_state
+                                .metadata
+                                .blocking_lock()
+                                .get(entry.filename.as_str())
+                                .is_some_and(|metadata| metadata.hash.is_none());
+                            if needs_image_facts {
+                                crate::metadata::update_blocking(&rayshot_state, |metadata| {
+                                    metadata
+                                        .entry(entry.filename.as_str())
+                                        .set_image(&image_buffer);
+                                });
+                            }
+                            *entry.demension.blocking_lock() = Some((
+                                image_buffer.width() as usize,
+                                image_buffer.height() as usize,
+                            ));
+                            let texture_handle = egui_ctx.load_texture(
+                                entry.filename.as_str(),
+                                eframe::epaint::ColorImage::from_rgba_unmultiplied(
+                                    [
+                                        image_buffer.width() as usize,
+                                        image_buffer.height() as usize,
+                                    ],
+                                    image_buffer.as_raw(),
+                                ),
+                                Default::default(),
+                            );
+                            entry.texture_handle.blocking_lock().replace(texture_handle);
+                        }
+                        Err(e) => {
+                            let err_str =
+                                format!("Failed to load screenshot '{}': {}", entry.filename, e);
+                            eprintln!("{}", err_str);
+                            rayshot_state.error_messages.blocking_lock().push(err_str);
+                            entry.state.blocking_lock().failed = true;
+                        }
+                    }
+                }
+                entry.state.blocking_lock().loading = false;
+                egui_ctx.request_repaint();
+            });
+        }
+        if any_busy {
+            self.texture_retry.notify_one();
+        }
+    }
+}
+
+// manages the texture cache again a little after it skipped busy entries, until they are loaded
+pub async fn run_texture_retry(rayshot_state: RayshotState, egui_ctx: eframe::egui::Context) {
+    loop {
+        rayshot_state.texture_retry.notified().await;
+        tokio::time::sleep(TEXTURE_RETRY_DELAY).await;
+        rayshot_state.manage_texture_cache(&egui_ctx).await;
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub enum RayshotHotkey {
+    CaptureScreenshot,
+    // captures the profile at this index regardless of the active one
+    CaptureProfile(usize),
+    Left,
+    Right,
+    Trash,
+    KeepBurstFrame,
+    Undo,
+    Redo,
+    // stars for the current screenshot
+    Rate(u8),
+    // adds the tag to the current screenshot or removes it
+    ToggleTag(String),
+}
+
+impl RayshotHotkey {
+    // runs what the hotkey does in the background, global hotkeys and window shortcuts alike
+    pub fn spawn_action(self, rayshot_state: RayshotState, egui_ctx: eframe::egui::Context) {
+        if matches!(
+            self,
+            RayshotHotkey::CaptureScreenshot | RayshotHotkey::CaptureProfile(_)
+        ) {
+            println!("Hotkey event detected: {:?}", self);
+        }
+        match self {
+            RayshotHotkey::CaptureScreenshot => {
+                tokio::task::spawn(async move {
+                    let profile_idx = *rayshot_state.active_profile_idx.lock().await;
+                    crate::capture::capture_screenshot(rayshot_state, egui_ctx, profile_idx).await;
+                });
+            }
+            RayshotHotkey::CaptureProfile(profile_idx) => {
+                tokio::task::spawn(crate::capture::capture_screenshot(
+                    rayshot_state,
+                    egui_ctx,
+                    profile_idx,
+                ));
+            }
+            RayshotHotkey::Left => {
+                tokio::task::spawn(async move {
+                    rayshot_state.try_decrement_screenshot_index().await;
+                    rayshot_state.manage_texture_cache(&egui_ctx).await;
+                    egui_ctx.request_repaint();
+                });
+            }
+            RayshotHotkey::Right => {
+                tokio::task::spawn(async move {
+                    rayshot_state.try_increment_screenshot_index().await;
+                    rayshot_state.manage_texture_cache(&egui_ctx).await;
+                    egui_ctx.request_repaint();
+                });
+            }
+            RayshotHotkey::Trash => {
+                tokio::task::spawn(async move {
+                    crate::storage::toggle_trash(&rayshot_state, &egui_ctx).await;
+                });
+            }
+            RayshotHotkey::KeepBurstFrame => {
+                tokio::task::spawn(async move {
+                    crate::storage::keep_burst_frame(&rayshot_state, &egui_ctx).await;
+                });
+            }
+            RayshotHotkey::Undo => {
+                tokio::task::spawn(async move {
+                    crate::journal::undo(&rayshot_state, &egui_ctx).await;
+                });
+            }
+            RayshotHotkey::Redo => {
+                tokio::task::spawn(async move {
+                    crate::journal::redo(&rayshot_state, &egui_ctx).await;
+                });
+            }
+            RayshotHotkey::Rate(rating) => {
+                tokio::task::spawn(async move {
+                    crate::metadata::rate_current(&rayshot_state, &egui_ctx, rating).await;
+                });
+            }
+            RayshotHotkey::ToggleTag(tag) => {
+                tokio::task::spawn(async move {
+                    crate::metadata::toggle_current_tag(&rayshot_state, &egui_ctx, &tag).await;
+                });
+            }
+        }
+    }
+}
+
+pub async fn report_error(
+    rayshot_state: &RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    error_msg: String,
+) {
+    eprintln!("{}", error_msg);
+    rayshot_state.error_messages.lock().await.push(error_msg);
+    egui_ctx.request_repaint();
+}
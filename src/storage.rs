@@ -0,0 +1,1835 @@
+// screenshots are saved as `{app}_{%Y%m%d_%H%M%S.%f}.{ext}`, the app name may contain underscores
+pub fn parse_screenshot_timestamp(filename: &str) -> Option<chrono::NaiveDateTime> {
+    let stem = std::path::Path::new(filename).file_stem()?.to_str()?;
+    let mut parts = stem.rsplitn(3, '_');
+    let time = parts.next()?;
+    let date = parts.next()?;
+    parts.next()?;
+    chrono::NaiveDateTime::parse_from_str(&format!("{}_{}", date, time), "%Y%m%d_%H%M%S.%f").ok()
+}
+
+pub fn scan_screenshot_dir(
+    config: &crate::config::RayshotConfig,
+    file_location: crate::FileLocation,
+) -> std::io::Result<Vec<(chrono::NaiveDateTime, crate::ScreenshotEntry)>> {
+    if matches!(file_location, crate::FileLocation::Trash)
+        && config.trash_backend == crate::config::TrashBackend::Freedesktop
+    {
+        return scan_freedesktop_trash(config);
+    }
+    let mut scanned = Vec::new();
+    walk_location(config, file_location, |dir_entry, subdir| {
+        scan_file(dir_entry, subdir, file_location, &mut scanned)
+    })?;
+    Ok(scanned)
+}
+
+// calls `visit` with every file in the tree below the location and its folder relative to it,
+// skipping hidden entries and the other location when it is nested inside this one
+fn walk_location(
+    config: &crate::config::RayshotConfig,
+    file_location: crate::FileLocation,
+    mut visit: impl FnMut(&std::fs::DirEntry, &std::path::Path) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let root = file_location.dir_path(config);
+    let other_root = match file_location {
+        crate::FileLocation::Local => crate::FileLocation::Trash,
+        crate::FileLocation::Trash => crate::FileLocation::Local,
+    }
+    .dir_path(config);
+
+    let mut pending_subdirs = vec![std::path::PathBuf::new()];
+    while let Some(subdir) = pending_subdirs.pop() {
+        let dir_entries = match std::fs::read_dir(root.join(&subdir)) {
+            Ok(dir_entries) => dir_entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for dir_entry in dir_entries {
+            let dir_entry = dir_entry?;
+            let name = dir_entry.file_name();
+            if name.to_string_lossy().starts_with('.') {
+                continue;
+            }
+            // symlinked folders are not followed so a link loop cannot hang the walk
+            let file_type = dir_entry.file_type()?;
+            if file_type.is_dir() && dir_entry.path() != other_root {
+                pending_subdirs.push(subdir.join(&name));
+            } else if file_type.is_file() {
+                visit(&dir_entry, &subdir)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn scan_file(
+    dir_entry: &std::fs::DirEntry,
+    subdir: &std::path::Path,
+    file_location: crate::FileLocation,
+    scanned: &mut Vec<(chrono::NaiveDateTime, crate::ScreenshotEntry)>,
+) -> std::io::Result<()> {
+    let path = dir_entry.path();
+    if image::ImageFormat::from_path(&path).is_err() {
+        return Ok(());
+    }
+    let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+        return Ok(());
+    };
+    let metadata = dir_entry.metadata()?;
+
+    // fall back to the modification time for files not named by rayshot
+    let Some(timestamp) = parse_screenshot_timestamp(filename).or_else(|| {
+        metadata
+            .modified()
+            .ok()
+            .map(|modified| chrono::DateTime::<chrono::Local>::from(modified).naive_local())
+    }) else {
+        return Ok(());
+    };
+
+    let entry = crate::ScreenshotEntry::new(
+        std::sync::Arc::new(subdir.join(filename).to_string_lossy().into_owned()),
+        file_location,
+    );
+    entry
+        .file_size
+        .blocking_lock()
+        .replace(metadata.len() as usize);
+    scanned.push((timestamp, entry));
+    Ok(())
+}
+
+fn scan_freedesktop_trash(
+    config: &crate::config::RayshotConfig,
+) -> std::io::Result<Vec<(chrono::NaiveDateTime, crate::ScreenshotEntry)>> {
+    let (trash, items) = freedesktop_trash_items(config)?;
+    let mut scanned = Vec::new();
+    for (relative_path, item) in items {
+        if image::ImageFormat::from_path(&relative_path).is_err() {
+            continue;
+        }
+        // the info file may outlive its file when something else emptied the trash
+        let Ok(metadata) = std::fs::metadata(trash.file_path(&item)) else {
+            continue;
+        };
+        let filename = relative_path.to_string_lossy().into_owned();
+        let timestamp = parse_screenshot_timestamp(&filename).unwrap_or(item.deleted_at);
+        let entry =
+            crate::ScreenshotEntry::new(std::sync::Arc::new(filename), crate::FileLocation::Trash);
+        entry
+            .file_size
+            .blocking_lock()
+            .replace(metadata.len() as usize);
+        scanned.push((timestamp, entry));
+    }
+    Ok(scanned)
+}
+
+// the items trashed from `screenshot_dir` with their paths relative to it, the most recently
+// trashed one when the same path was trashed several times
+fn freedesktop_trash_items(
+    config: &crate::config::RayshotConfig,
+) -> std::io::Result<(
+    crate::trash::FreedesktopTrash,
+    Vec<(std::path::PathBuf, crate::trash::TrashItem)>,
+)> {
+    let trash = crate::trash::FreedesktopTrash::home()?;
+    let screenshot_dir = std::path::absolute(&config.screenshot_dir)?;
+    let mut items = std::collections::HashMap::new();
+    for item in trash.items()? {
+        let Ok(relative_path) = item.original_path.strip_prefix(&screenshot_dir) else {
+            continue;
+        };
+        let relative_path = relative_path.to_path_buf();
+        if items
+            .get(&relative_path)
+            .is_none_or(|newer: &crate::trash::TrashItem| newer.deleted_at < item.deleted_at)
+        {
+            items.insert(relative_path, item);
+        }
+    }
+    Ok((trash, items.into_iter().collect()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Png,
+    Jpeg,
+    WebP,
+    Avif,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 4] = [
+        OutputFormat::Png,
+        OutputFormat::Jpeg,
+        OutputFormat::WebP,
+        OutputFormat::Avif,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg => "jpg",
+            OutputFormat::WebP => "webp",
+            OutputFormat::Avif => "avif",
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "png" => Ok(OutputFormat::Png),
+            "jpg" | "jpeg" => Ok(OutputFormat::Jpeg),
+            "webp" => Ok(OutputFormat::WebP),
+            "avif" => Ok(OutputFormat::Avif),
+            _ => Err(format!(
+                "Unsupported format '{}', expected png, jpg, webp or avif",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PngCompression {
+    #[default]
+    Fast,
+    Default,
+    Best,
+}
+
+impl From<PngCompression> for image::codecs::png::CompressionType {
+    fn from(compression: PngCompression) -> Self {
+        match compression {
+            PngCompression::Fast => image::codecs::png::CompressionType::Fast,
+            PngCompression::Default => image::codecs::png::CompressionType::Default,
+            PngCompression::Best => image::codecs::png::CompressionType::Best,
+        }
+    }
+}
+
+const DEFAULT_JPEG_QUALITY: u8 = 90;
+const DEFAULT_AVIF_QUALITY: u8 = 80;
+const DEFAULT_AVIF_SPEED: u8 = 6;
+
+fn default_jpeg_quality() -> u8 {
+    DEFAULT_JPEG_QUALITY
+}
+
+fn default_avif_quality() -> u8 {
+    DEFAULT_AVIF_QUALITY
+}
+
+fn default_avif_speed() -> u8 {
+    DEFAULT_AVIF_SPEED
+}
+
+// the format a profile saves in along with the settings of its encoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(tag = "format", rename_all = "lowercase", deny_unknown_fields)]
+pub enum EncoderConfig {
+    Png {
+        #[serde(default)]
+        compression: PngCompression,
+    },
+    #[serde(alias = "jpg")]
+    Jpeg {
+        #[serde(default = "default_jpeg_quality")]
+        quality: u8,
+    },
+    // lossless without `quality`, lossy through libwebp with it
+    Webp {
+        #[serde(default)]
+        quality: Option<u8>,
+    },
+    Avif {
+        #[serde(default = "default_avif_quality")]
+        quality: u8,
+        // 1 is the slowest and smallest, 10 the fastest
+        #[serde(default = "default_avif_speed")]
+        speed: u8,
+    },
+}
+
+impl Default for EncoderConfig {
+    fn default() -> Self {
+        OutputFormat::Png.into()
+    }
+}
+
+impl From<OutputFormat> for EncoderConfig {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Png => EncoderConfig::Png {
+                compression: PngCompression::default(),
+            },
+            OutputFormat::Jpeg => EncoderConfig::Jpeg {
+                quality: DEFAULT_JPEG_QUALITY,
+            },
+            OutputFormat::WebP => EncoderConfig::Webp { quality: None },
+            OutputFormat::Avif => EncoderConfig::Avif {
+                quality: DEFAULT_AVIF_QUALITY,
+                speed: DEFAULT_AVIF_SPEED,
+            },
+        }
+    }
+}
+
+impl EncoderConfig {
+    pub fn format(&self) -> OutputFormat {
+        match self {
+            EncoderConfig::Png { .. } => OutputFormat::Png,
+            EncoderConfig::Jpeg { .. } => OutputFormat::Jpeg,
+            EncoderConfig::Webp { .. } => OutputFormat::WebP,
+            EncoderConfig::Avif { .. } => OutputFormat::Avif,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            EncoderConfig::Png { .. } => Ok(()),
+            EncoderConfig::Jpeg { quality }
+            | EncoderConfig::Webp {
+                quality: Some(quality),
+            }
+            | EncoderConfig::Avif { quality, .. }
+                if !(1..=100).contains(&quality) =>
+            {
+                Err(format!(
+                    "quality must be between 1 and 100, got {}",
+                    quality
+                ))
+            }
+            EncoderConfig::Avif { speed, .. } if !(1..=10).contains(&speed) => {
+                Err(format!("speed must be between 1 and 10, got {}", speed))
+            }
+            EncoderConfig::Jpeg { .. }
+            | EncoderConfig::Webp { .. }
+            | EncoderConfig::Avif { .. } => Ok(()),
+        }
+    }
+}
+
+const COUNTER_FILE_NAME: &str = ".rayshot-counter";
+
+// burst frames claim counter values from several threads at once
+static COUNTER_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+// the `{counter}` value, kept next to the screenshots so it keeps growing across sessions
+pub fn next_counter(config: &crate::config::RayshotConfig) -> std::io::Result<u64> {
+    let _counter_lock = COUNTER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = config.screenshot_dir.join(COUNTER_FILE_NAME);
+    let current = match std::fs::read_to_string(&path) {
+        Ok(content) => content.trim().parse::<u64>().map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid counter file '{}': {}", path.display(), e),
+            )
+        })?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(e),
+    };
+    std::fs::create_dir_all(&config.screenshot_dir)?;
+    std::fs::write(&path, (current + 1).to_string())?;
+    Ok(current + 1)
+}
+
+// renders the directory and filename templates and claims a path relative to `dir` that is
+// free in `dir` and `also_free_in` by creating an empty placeholder file, `-2`, `-3`, ... are
+// appended to the stem on collisions
+#[allow(clippy::too_many_arguments)]
+pub fn claim_filename(
+    config: &crate::config::RayshotConfig,
+    profile: &crate::config::CaptureProfile,
+    names: &crate::capture::CaptureNames,
+    image_buffer: Option<&image::RgbaImage>,
+    format: OutputFormat,
+    dir: &std::path::Path,
+    also_free_in: Option<&std::path::Path>,
+) -> std::io::Result<String> {
+    let uses_counter = config.filename_template.uses_counter()
+        || config
+            .directory_template
+            .as_ref()
+            .is_some_and(|template| template.uses_counter());
+    let counter = if uses_counter {
+        next_counter(config)?
+    } else {
+        0
+    };
+    let context = crate::filename::FilenameContext {
+        app: &names.app,
+        title: &names.title,
+        profile: &profile.name,
+        time: chrono::Local::now(),
+        counter,
+        image: image_buffer,
+    };
+    let stem = config.filename_template.render(&context);
+    let subdir = config
+        .directory_template
+        .as_ref()
+        .map(|template| template.render(&context))
+        .unwrap_or_default();
+
+    claim_free_filename(dir, also_free_in, &subdir, &stem, format.extension())
+}
+
+// claims `subdir/stem.extension` below `dir`, or the first of `-2`, `-3`, ... appended to the
+// stem that is free in `dir` and `also_free_in`, by creating an empty placeholder file
+fn claim_free_filename(
+    dir: &std::path::Path,
+    also_free_in: Option<&std::path::Path>,
+    subdir: &std::path::Path,
+    stem: &str,
+    extension: &str,
+) -> std::io::Result<String> {
+    std::fs::create_dir_all(dir.join(subdir))?;
+    let mut attempt = 1;
+    loop {
+        let filename = subdir.join(match attempt {
+            1 => format!("{}.{}", stem, extension),
+            _ => format!("{}-{}.{}", stem, attempt, extension),
+        });
+        attempt += 1;
+        if also_free_in.is_some_and(|other_dir| other_dir.join(&filename).exists()) {
+            continue;
+        }
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(&filename))
+        {
+            Ok(_) => return Ok(filename.to_string_lossy().into_owned()),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// relative to `screenshot_dir`, so it includes the profile's output subfolder and extension,
+// kept free in the trash folder too so trashing or restoring never overwrites a file
+pub fn claim_profile_filename(
+    config: &crate::config::RayshotConfig,
+    profile: &crate::config::CaptureProfile,
+    names: &crate::capture::CaptureNames,
+    image_buffer: Option<&image::RgbaImage>,
+) -> std::io::Result<String> {
+    let subdir = profile.output_subdir.clone().unwrap_or_default();
+    let filename = claim_filename(
+        config,
+        profile,
+        names,
+        image_buffer,
+        profile.encoder.format(),
+        &config.screenshot_dir.join(&subdir),
+        (config.trash_backend == crate::config::TrashBackend::Folder)
+            .then(|| config.trash_dir.join(&subdir))
+            .as_deref(),
+    )?;
+    Ok(subdir.join(filename).to_string_lossy().into_owned())
+}
+
+// written next to `path` and renamed over it, so a crash never leaves half a file
+pub fn write_atomically(path: &std::path::Path, content: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&temp_path, content)?;
+    std::fs::rename(&temp_path, path)
+}
+
+const SHARED_LOCK_FILE_NAME: &str = ".rayshot.lock";
+
+// held by the GUI and the CLI while they read the metadata index or the undo journal and write it
+// back, so neither drops what the other wrote in between, released when the file is dropped
+pub fn lock_shared_files(config: &crate::config::RayshotConfig) -> std::io::Result<std::fs::File> {
+    std::fs::create_dir_all(&config.screenshot_dir)?;
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(config.screenshot_dir.join(SHARED_LOCK_FILE_NAME))?;
+    file.lock()?;
+    Ok(file)
+}
+
+pub fn save_image(
+    image_buffer: &image::RgbaImage,
+    path: &std::path::Path,
+    encoder: EncoderConfig,
+) -> image::ImageResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(image::ImageError::IoError)?;
+    }
+    let mut writer =
+        std::io::BufWriter::new(std::fs::File::create(path).map_err(image::ImageError::IoError)?);
+    match encoder {
+        EncoderConfig::Png { compression } => {
+            image_buffer.write_with_encoder(image::codecs::png::PngEncoder::new_with_quality(
+                &mut writer,
+                compression.into(),
+                image::codecs::png::FilterType::Adaptive,
+            ))
+        }
+        // jpeg has no alpha channel
+        EncoderConfig::Jpeg { quality } => image::DynamicImage::ImageRgba8(image_buffer.clone())
+            .to_rgb8()
+            .write_with_encoder(image::codecs::jpeg::JpegEncoder::new_with_quality(
+                &mut writer,
+                quality,
+            )),
+        EncoderConfig::Webp { quality: None } => image_buffer
+            .write_with_encoder(image::codecs::webp::WebPEncoder::new_lossless(&mut writer)),
+        // the image crate has no lossy WebP encoder
+        EncoderConfig::Webp {
+            quality: Some(quality),
+        } => {
+            let encoded = webp::Encoder::from_rgba(
+                image_buffer.as_raw(),
+                image_buffer.width(),
+                image_buffer.height(),
+            )
+            .encode_simple(false, f32::from(quality))
+            .map_err(|e| {
+                image::ImageError::Encoding(image::error::EncodingError::new(
+                    image::ImageFormat::WebP.into(),
+                    format!("libwebp failed with {:?}", e),
+                ))
+            })?;
+            std::io::Write::write_all(&mut writer, &encoded).map_err(image::ImageError::IoError)
+        }
+        EncoderConfig::Avif { quality, speed } => image_buffer.write_with_encoder(
+            image::codecs::avif::AvifEncoder::new_with_speed_quality(&mut writer, speed, quality),
+        ),
+    }?;
+    std::io::Write::flush(&mut writer).map_err(image::ImageError::IoError)
+}
+
+pub fn ensure_directories(config: &crate::config::RayshotConfig) -> std::io::Result<()> {
+    let trash_dir =
+        (config.trash_backend == crate::config::TrashBackend::Folder).then_some(&config.trash_dir);
+    for dir in std::iter::once(&config.screenshot_dir).chain(trash_dir) {
+        if !dir.exists() {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                std::io::Error::new(
+                    e.kind(),
+                    format!("Failed to create directory '{}': {}", dir.display(), e),
+                )
+            })?;
+            eprintln!("Created directory: {}", dir.display());
+        }
+    }
+    Ok(())
+}
+
+// moves the file between the screenshot and trash directories, keeping its relative path and
+// removing the folders it leaves empty, returning where it ended up
+pub fn move_screenshot(
+    config: &crate::config::RayshotConfig,
+    trash: Option<&crate::trash::FreedesktopTrash>,
+    filename: &str,
+    current_location: crate::FileLocation,
+) -> std::io::Result<crate::FileLocation> {
+    let target_location = match current_location {
+        crate::FileLocation::Local => crate::FileLocation::Trash,
+        crate::FileLocation::Trash => crate::FileLocation::Local,
+    };
+    if config.trash_backend == crate::config::TrashBackend::Freedesktop {
+        move_freedesktop_screenshot(config, trash, filename, current_location)?;
+        return Ok(target_location);
+    }
+    let target_dir = target_location.dir_path(config);
+    let current_path = current_location.dir_path(config).join(filename);
+    let target_path = target_dir.join(filename);
+    if let Some(parent) = target_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(&current_path, &target_path).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!(
+                "Failed to move screenshot '{}' to '{}': {}",
+                filename,
+                target_dir.display(),
+                e
+            ),
+        )
+    })?;
+    remove_empty_parents(current_location.dir_path(config), &current_path);
+    Ok(target_location)
+}
+
+fn move_freedesktop_screenshot(
+    config: &crate::config::RayshotConfig,
+    trash: Option<&crate::trash::FreedesktopTrash>,
+    filename: &str,
+    current_location: crate::FileLocation,
+) -> std::io::Result<()> {
+    let trash = crate::trash::FreedesktopTrash::shared_or_home(trash)?;
+    let path = config.screenshot_dir.join(filename);
+    let (result, action) = match current_location {
+        crate::FileLocation::Local => (trash.trash(&path), "move screenshot"),
+        crate::FileLocation::Trash => (trash.restore(&path), "restore screenshot"),
+    };
+    result.map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!(
+                "Failed to {} '{}' with the desktop trash: {}",
+                action, filename, e
+            ),
+        )
+    })?;
+    if matches!(current_location, crate::FileLocation::Local) {
+        remove_empty_parents(&config.screenshot_dir, &path);
+    }
+    Ok(())
+}
+
+// best effort, stops at the first folder that still has files or cannot be removed
+fn remove_empty_parents(root: &std::path::Path, path: &std::path::Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) || std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+struct TrashedFile {
+    // relative to the location it was trashed from
+    relative_path: std::path::PathBuf,
+    trashed_at: std::time::SystemTime,
+    size: u64,
+    // `None` in the trash folder
+    freedesktop_item: Option<crate::trash::TrashItem>,
+}
+
+fn local_system_time(time: chrono::NaiveDateTime) -> std::time::SystemTime {
+    time.and_local_timezone(chrono::Local)
+        .earliest()
+        .map_or_else(|| time.and_utc().into(), Into::into)
+}
+
+// the screenshots in the trash, other files there are never purged, `trash_times` are the ones
+// the metadata index knows, the trash folder falls back to the file's modification time for the
+// others
+fn list_trashed_files(
+    config: &crate::config::RayshotConfig,
+    trash_times: &std::collections::HashMap<String, chrono::NaiveDateTime>,
+) -> std::io::Result<Vec<TrashedFile>> {
+    let mut trashed = Vec::new();
+    if config.trash_backend == crate::config::TrashBackend::Freedesktop {
+        let (trash, items) = freedesktop_trash_items(config)?;
+        for (relative_path, item) in items {
+            if image::ImageFormat::from_path(&relative_path).is_err() {
+                continue;
+            }
+            let size =
+                std::fs::metadata(trash.file_path(&item)).map_or(0, |metadata| metadata.len());
+            trashed.push(TrashedFile {
+                relative_path,
+                trashed_at: local_system_time(item.deleted_at),
+                size,
+                freedesktop_item: Some(item),
+            });
+        }
+        return Ok(trashed);
+    }
+    walk_location(config, crate::FileLocation::Trash, |dir_entry, subdir| {
+        let relative_path = subdir.join(dir_entry.file_name());
+        if image::ImageFormat::from_path(&relative_path).is_err() {
+            return Ok(());
+        }
+        let metadata = dir_entry.metadata()?;
+        let trashed_at = match trash_times.get(relative_path.to_string_lossy().as_ref()) {
+            Some(trashed_at) => local_system_time(*trashed_at),
+            None => metadata.modified()?,
+        };
+        trashed.push(TrashedFile {
+            relative_path,
+            trashed_at,
+            size: metadata.len(),
+            freedesktop_item: None,
+        });
+        Ok(())
+    })?;
+    Ok(trashed)
+}
+
+// `false` when the file is no longer in the trash, e.g. because it was restored meanwhile
+fn delete_trashed_file(
+    config: &crate::config::RayshotConfig,
+    file: &TrashedFile,
+) -> std::io::Result<bool> {
+    if let Some(item) = &file.freedesktop_item {
+        return crate::trash::FreedesktopTrash::home()?.delete(item);
+    }
+    let path = config.trash_dir.join(&file.relative_path);
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    }
+    remove_empty_parents(&config.trash_dir, &path);
+    Ok(true)
+}
+
+// what a purge deleted, a failed deletion is recorded and does not stop the others
+#[derive(Default)]
+pub struct TrashPurge {
+    // paths relative to the location they were trashed from
+    pub purged: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl TrashPurge {
+    fn delete(&mut self, config: &crate::config::RayshotConfig, file: &TrashedFile) {
+        match delete_trashed_file(config, file) {
+            Ok(true) => self
+                .purged
+                .push(file.relative_path.to_string_lossy().into_owned()),
+            Ok(false) => {}
+            Err(e) => self.errors.push(format!(
+                "Failed to delete '{}' from the trash: {}",
+                file.relative_path.display(),
+                e
+            )),
+        }
+    }
+}
+
+// the trashed files to delete for good, newest are kept until one would break a limit of
+// `retention`, that file and every older one are deleted
+fn plan_trash_purge(
+    config: &crate::config::RayshotConfig,
+    retention: &crate::config::TrashRetention,
+    trash_times: &std::collections::HashMap<String, chrono::NaiveDateTime>,
+) -> std::io::Result<Vec<TrashedFile>> {
+    let mut trashed = list_trashed_files(config, trash_times)?;
+    trashed.sort_by_key(|file| std::cmp::Reverse(file.trashed_at));
+
+    let now = std::time::SystemTime::now();
+    let max_age = retention
+        .max_age_days
+        .map(|days| std::time::Duration::from_secs(days * 24 * 60 * 60));
+    let (mut kept_count, mut kept_bytes) = (0, 0);
+    let mut purging = false;
+    Ok(trashed
+        .into_iter()
+        .filter(|file| {
+            if !purging {
+                let too_old = max_age.is_some_and(|max_age| {
+                    now.duration_since(file.trashed_at)
+                        .is_ok_and(|age| age > max_age)
+                });
+                let too_many = retention
+                    .max_count
+                    .is_some_and(|max_count| kept_count >= max_count);
+                let too_big = retention
+                    .max_total_bytes
+                    .is_some_and(|max_total_bytes| kept_bytes + file.size > max_total_bytes);
+                purging = too_old || too_many || too_big;
+            }
+            if !purging {
+                kept_count += 1;
+                kept_bytes += file.size;
+            }
+            purging
+        })
+        .collect())
+}
+
+// deletes the trashed files `plan_trash_purge` picks, for the CLI, which shows no entries
+pub fn purge_trash(
+    config: &crate::config::RayshotConfig,
+    retention: &crate::config::TrashRetention,
+    trash_times: &std::collections::HashMap<String, chrono::NaiveDateTime>,
+) -> std::io::Result<TrashPurge> {
+    let mut purge = TrashPurge::default();
+    for file in plan_trash_purge(config, retention, trash_times)? {
+        purge.delete(config, &file);
+    }
+    Ok(purge)
+}
+
+async fn purge_trash_entries(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    retention: crate::config::TrashRetention,
+) {
+    let config = rayshot_state.config.clone();
+    let trash_times = rayshot_state.metadata.lock().await.trash_times();
+    let planned = match tokio::task::spawn_blocking(move || {
+        plan_trash_purge(&config, &retention, &trash_times)
+    })
+    .await
+    {
+        Ok(Ok(planned)) => planned,
+        Ok(Err(e)) => {
+            return crate::state::report_error(
+                rayshot_state,
+                egui_ctx,
+                format!("Failed to purge the trash: {}", e),
+            )
+            .await;
+        }
+        Err(e) => {
+            return crate::state::report_error(
+                rayshot_state,
+                egui_ctx,
+                format!("Trash purge task failed: {}", e),
+            )
+            .await;
+        }
+    };
+
+    let mut purge = TrashPurge::default();
+    for file in planned {
+        let filename = file.relative_path.to_string_lossy();
+        let entry = rayshot_state
+            .screenshot_entries
+            .lock()
+            .await
+            .iter()
+            .find(|entry| *entry.filename == filename)
+            .cloned();
+        // a shown file is deleted under its lock and only while it is still trashed
+        let _file_lock = match &entry {
+            Some(entry) => {
+                let file_lock = entry.file_lock.lock().await;
+                if *entry.file_location.lock().await != crate::FileLocation::Trash {
+                    continue;
+                }
+                Some(file_lock)
+            }
+            None => None,
+        };
+        let config = rayshot_state.config.clone();
+        match tokio::task::spawn_blocking(move || {
+            let mut purge = TrashPurge::default();
+            purge.delete(&config, &file);
+            purge
+        })
+        .await
+        {
+            Ok(file_purge) => {
+                purge.purged.extend(file_purge.purged);
+                purge.errors.extend(file_purge.errors);
+            }
+            Err(e) => purge.errors.push(format!("Trash purge task failed: {}", e)),
+        }
+    }
+    let TrashPurge { purged, errors } = purge;
+    for e in errors {
+        crate::state::report_error(rayshot_state, egui_ctx, e).await;
+    }
+    if purged.is_empty() {
+        return;
+    }
+    eprintln!("Deleted {} screenshot(s) from the trash", purged.len());
+    crate::metadata::update(rayshot_state, egui_ctx, |metadata| {
+        for filename in &purged {
+            metadata.remove(filename);
+        }
+    })
+    .await;
+    rayshot_state
+        .remove_entries(
+            &purged.into_iter().collect(),
+            Some(crate::FileLocation::Trash),
+        )
+        .await;
+    rayshot_state.manage_texture_cache(egui_ctx).await;
+    egui_ctx.request_repaint();
+}
+
+// applies `trash_retention` now and then every `check_interval_minutes`
+pub async fn run_trash_retention(
+    rayshot_state: crate::RayshotState,
+    egui_ctx: eframe::egui::Context,
+) {
+    let retention = rayshot_state.config.trash_retention.clone();
+    if !retention.is_enabled() {
+        return;
+    }
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+        retention.check_interval_minutes * 60,
+    ));
+    loop {
+        interval.tick().await;
+        purge_trash_entries(&rayshot_state, &egui_ctx, retention.clone()).await;
+    }
+}
+
+// deletes every trashed screenshot for good
+pub async fn empty_trash(rayshot_state: &crate::RayshotState, egui_ctx: &eframe::egui::Context) {
+    let everything = crate::config::TrashRetention {
+        max_count: Some(0),
+        ..Default::default()
+    };
+    purge_trash_entries(rayshot_state, egui_ctx, everything).await;
+}
+
+// the file move behind the Trash hotkey, toggles the current entry between local and trash
+pub async fn toggle_trash(rayshot_state: &crate::RayshotState, egui_ctx: &eframe::egui::Context) {
+    let Some(current_entry) = rayshot_state.get_current_screenshot().await else {
+        return crate::state::report_error(
+            rayshot_state,
+            egui_ctx,
+            "No current screenshot to move to trash".to_string(),
+        )
+        .await;
+    };
+    let operation = move_entry(rayshot_state, egui_ctx, &current_entry).await;
+    crate::metadata::save(rayshot_state, egui_ctx).await;
+    if let Some(operation) = operation {
+        crate::journal::record(rayshot_state, egui_ctx, vec![operation]).await;
+    }
+}
+
+// trashes every other local frame of the current screenshot's burst
+pub async fn keep_burst_frame(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+) {
+    let Some(current_entry) = rayshot_state.get_current_screenshot().await else {
+        return crate::state::report_error(
+            rayshot_state,
+            egui_ctx,
+            "No current screenshot to keep".to_string(),
+        )
+        .await;
+    };
+    let Some(burst_id) = current_entry.burst_id else {
+        return crate::state::report_error(
+            rayshot_state,
+            egui_ctx,
+            format!(
+                "Screenshot '{}' is not part of a burst",
+                current_entry.filename
+            ),
+        )
+        .await;
+    };
+
+    let other_frames: Vec<_> = rayshot_state
+        .screenshot_entries
+        .lock()
+        .await
+        .iter()
+        .filter(|entry| {
+            entry.burst_id == Some(burst_id)
+                && !std::sync::Arc::ptr_eq(&entry.filename, &current_entry.filename)
+        })
+        .cloned()
+        .collect();
+    // undone as one step
+    let mut operations = Vec::new();
+    for entry in other_frames {
+        if matches!(
+            *entry.file_location.lock().await,
+            crate::FileLocation::Local
+        ) {
+            operations.extend(move_entry(rayshot_state, egui_ctx, &entry).await);
+        }
+    }
+    crate::metadata::save(rayshot_state, egui_ctx).await;
+    crate::journal::record(rayshot_state, egui_ctx, operations).await;
+}
+
+// toggles the entry between local and trash, returning the move for the journal, the caller
+// saves the metadata index once all of its moves are done
+pub async fn move_entry(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    current_entry: &crate::ScreenshotEntry,
+) -> Option<crate::journal::FileOperation> {
+    current_entry.state.lock().await.moving = true;
+    {
+        let _file_lock = current_entry.file_lock.lock().await;
+        egui_ctx.request_repaint();
+        let current_location = *current_entry.file_location.lock().await;
+        match move_screenshot(
+            &rayshot_state.config,
+            rayshot_state.freedesktop_trash.as_ref(),
+            current_entry.filename.as_str(),
+            current_location,
+        ) {
+            Ok(target_location) => {
+                *current_entry.file_location.lock().await = target_location;
+                crate::metadata::change(rayshot_state, |metadata| {
+                    metadata
+                        .entry(current_entry.filename.as_str())
+                        .set_location(target_location);
+                })
+                .await;
+            }
+            Err(e) => {
+                current_entry.state.lock().await.failed = true;
+                crate::state::report_error(rayshot_state, egui_ctx, e.to_string()).await;
+                return None;
+            }
+        }
+        current_entry.state.lock().await.moving = false;
+        egui_ctx.request_repaint();
+        Some(crate::journal::FileOperation::Move {
+            filename: current_entry.filename.to_string(),
+            from: current_location,
+        })
+    }
+}
+
+// `filename` with the stem typed by the user, in the same folder and with the same extension
+pub fn renamed_filename(filename: &str, stem: &str) -> Result<String, String> {
+    if stem.trim().is_empty() {
+        return Err("the new name must not be empty".to_string());
+    }
+    let path = std::path::Path::new(filename);
+    let stem = crate::filename::user_stem(stem);
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}.{}", stem, extension.to_string_lossy()),
+        None => stem,
+    };
+    Ok(path
+        .with_file_name(file_name)
+        .to_string_lossy()
+        .into_owned())
+}
+
+// renames the file within its folder, never replacing another one, files in the desktop trash
+// are found by their original name so they keep it
+pub fn rename_screenshot(
+    config: &crate::config::RayshotConfig,
+    location: crate::FileLocation,
+    from: &str,
+    to: &str,
+) -> std::io::Result<()> {
+    if matches!(location, crate::FileLocation::Trash)
+        && config.trash_backend == crate::config::TrashBackend::Freedesktop
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("'{}' is in the desktop trash, restore it first", from),
+        ));
+    }
+    let dir = location.dir_path(config);
+    let to_path = dir.join(to);
+    if to_path.exists() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("'{}' already exists", to),
+        ));
+    }
+    std::fs::rename(dir.join(from), &to_path).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!("Failed to rename '{}' to '{}': {}", from, to, e),
+        )
+    })
+}
+
+// renames the entry's file and moves its record along, returning the rename for the journal
+pub async fn rename_entry(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+    to: String,
+) -> Option<crate::journal::FileOperation> {
+    let from = entry.filename.to_string();
+    {
+        let _file_lock = entry.file_lock.lock().await;
+        let location = *entry.file_location.lock().await;
+        let config = rayshot_state.config.clone();
+        let (task_from, task_to) = (from.clone(), to.clone());
+        let result = tokio::task::spawn_blocking(move || {
+            rename_screenshot(&config, location, &task_from, &task_to)
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        if let Err(e) = result {
+            crate::state::report_error(rayshot_state, egui_ctx, e.to_string()).await;
+            return None;
+        }
+
+        // the renamed entry shares the file lock, state and texture with the old one
+        let mut renamed = entry.clone();
+        renamed.filename = std::sync::Arc::new(to.clone());
+        if let Some(slot) = rayshot_state
+            .screenshot_entries
+            .lock()
+            .await
+            .iter_mut()
+            .find(|other| std::sync::Arc::ptr_eq(&other.filename, &entry.filename))
+        {
+            *slot = renamed;
+        }
+        crate::metadata::update(rayshot_state, egui_ctx, |metadata| {
+            if let Some(record) = metadata.remove(&from) {
+                *metadata.entry(&to) = record;
+            }
+        })
+        .await;
+    }
+    egui_ctx.request_repaint();
+    Some(crate::journal::FileOperation::Rename { from, to })
+}
+
+// rewrites the entry's file with `edits` applied and moves its annotations along, returning the
+// edit for the journal
+pub async fn edit_entry(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+    edits: Vec<crate::edit::Edit>,
+) -> Option<crate::journal::FileOperation> {
+    entry.state.lock().await.writing = true;
+    egui_ctx.request_repaint();
+    let result = {
+        let _file_lock = entry.file_lock.lock().await;
+        let file_location = *entry.file_location.lock().await;
+        let config = rayshot_state.config.clone();
+        let trash = rayshot_state.freedesktop_trash.clone();
+        let filename = entry.filename.clone();
+        let edits = edits.clone();
+        tokio::task::spawn_blocking(move || {
+            let path = file_location.file_path(&config, trash.as_ref(), &filename)?;
+            let (backup, (width, height), edited) =
+                edit_screenshot(&config, &path, &filename, &edits)?;
+            let file_size = std::fs::metadata(&path)?.len();
+            Ok::<_, std::io::Error>((backup, (width, height), edited.dimensions(), file_size))
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+    };
+    entry.state.lock().await.writing = false;
+    let (backup, (width, height), dimensions, file_size) = match result {
+        Ok(edited) => edited,
+        Err(e) => {
+            crate::state::report_error(
+                rayshot_state,
+                egui_ctx,
+                format!("Failed to edit '{}': {}", entry.filename, e),
+            )
+            .await;
+            return None;
+        }
+    };
+
+    let before = rayshot_state
+        .metadata
+        .lock()
+        .await
+        .get(entry.filename.as_str())
+        .map(|metadata| metadata.annotations.clone())
+        .unwrap_or_default();
+    // the edits already worked on the image, so they fit the annotations too
+    let after =
+        crate::edit::transform_annotations(&before, &edits, width, height).unwrap_or_default();
+    refresh_edited_entry(
+        rayshot_state,
+        egui_ctx,
+        entry,
+        dimensions,
+        file_size,
+        after.clone(),
+    )
+    .await;
+    Some(crate::journal::FileOperation::Edit {
+        filename: entry.filename.to_string(),
+        backup,
+        before,
+        after,
+    })
+}
+
+// swaps the entry's file with an edit backup for undo and redo, `annotations` belong to the
+// version it brings back
+pub async fn swap_entry_edit(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+    backup: &str,
+    annotations: &[crate::annotation::Annotation],
+) -> bool {
+    entry.state.lock().await.writing = true;
+    egui_ctx.request_repaint();
+    let result = {
+        let _file_lock = entry.file_lock.lock().await;
+        let file_location = *entry.file_location.lock().await;
+        let config = rayshot_state.config.clone();
+        let trash = rayshot_state.freedesktop_trash.clone();
+        let filename = entry.filename.clone();
+        let backup = backup.to_string();
+        tokio::task::spawn_blocking(move || {
+            let path = file_location.file_path(&config, trash.as_ref(), &filename)?;
+            swap_edit_backup(&config, &path, &backup)?;
+            let dimensions = image::image_dimensions(&path).map_err(std::io::Error::other)?;
+            let file_size = std::fs::metadata(&path)?.len();
+            Ok::<_, std::io::Error>((dimensions, file_size))
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+    };
+    entry.state.lock().await.writing = false;
+    match result {
+        Ok((dimensions, file_size)) => {
+            refresh_edited_entry(
+                rayshot_state,
+                egui_ctx,
+                entry,
+                dimensions,
+                file_size,
+                annotations.to_vec(),
+            )
+            .await;
+            true
+        }
+        Err(e) => {
+            crate::state::report_error(
+                rayshot_state,
+                egui_ctx,
+                format!(
+                    "Failed to swap '{}' with its edit backup: {}",
+                    entry.filename, e
+                ),
+            )
+            .await;
+            false
+        }
+    }
+}
+
+// the record keeps the tags and notes, the hash and texture come back with the next load
+async fn refresh_edited_entry(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+    (width, height): (u32, u32),
+    file_size: u64,
+    annotations: Vec<crate::annotation::Annotation>,
+) {
+    crate::metadata::update(rayshot_state, egui_ctx, |metadata| {
+        let metadata = metadata.entry(entry.filename.as_str());
+        metadata.width = Some(width);
+        metadata.height = Some(height);
+        metadata.hash = None;
+        metadata.file_size = Some(file_size);
+        metadata.annotations = annotations;
+    })
+    .await;
+    *entry.demension.lock().await = Some((width as usize, height as usize));
+    *entry.file_size.lock().await = Some(file_size as usize);
+    *entry.texture_handle.lock().await = None;
+    rayshot_state.manage_texture_cache(egui_ctx).await;
+    egui_ctx.request_repaint();
+}
+
+// copies `source` into `dir`, or decodes and saves it with `encoder`, keeping the screenshot's
+// relative path and never overwriting, returns the path written
+pub fn export_screenshot(
+    source: &std::path::Path,
+    filename: &str,
+    dir: &std::path::Path,
+    encoder: Option<EncoderConfig>,
+) -> std::io::Result<std::path::PathBuf> {
+    let path = std::path::Path::new(filename);
+    let subdir = path.parent().unwrap_or(std::path::Path::new(""));
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = match encoder {
+        Some(encoder) => encoder.format().extension().to_string(),
+        None => path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+    };
+    let target = dir.join(claim_free_filename(dir, None, subdir, &stem, &extension)?);
+    let result = match encoder {
+        None => std::fs::copy(source, &target).map(|_| ()),
+        Some(encoder) => image::open(source)
+            .and_then(|image| save_image(&image.to_rgba8(), &target, encoder))
+            .map_err(std::io::Error::other),
+    };
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&target);
+        return Err(e);
+    }
+    Ok(target)
+}
+
+// saves the frame of the capture `filename` from before redaction next to it as
+// `<name>-unredacted`, never overwriting, returns its path relative to `dir`
+pub fn save_unredacted(
+    dir: &std::path::Path,
+    also_free_in: Option<&std::path::Path>,
+    filename: &str,
+    image_buffer: &image::RgbaImage,
+    encoder: EncoderConfig,
+) -> std::io::Result<String> {
+    let path = std::path::Path::new(filename);
+    let subdir = path.parent().unwrap_or(std::path::Path::new(""));
+    let stem = format!(
+        "{}-unredacted",
+        path.file_stem().unwrap_or_default().to_string_lossy()
+    );
+    let original = claim_free_filename(
+        dir,
+        also_free_in,
+        subdir,
+        &stem,
+        encoder.format().extension(),
+    )?;
+    if let Err(e) = save_image(image_buffer, &dir.join(&original), encoder) {
+        let _ = std::fs::remove_file(dir.join(&original));
+        return Err(std::io::Error::other(e));
+    }
+    Ok(original)
+}
+
+// saves a copy of `source` with `annotations` burnt in as `<name>-annotated` into `dir`, in the
+// source's format, keeping the screenshot's relative path and never overwriting
+pub fn export_annotated(
+    source: &std::path::Path,
+    filename: &str,
+    dir: &std::path::Path,
+    annotations: &[crate::annotation::Annotation],
+) -> std::io::Result<std::path::PathBuf> {
+    let path = std::path::Path::new(filename);
+    let subdir = path.parent().unwrap_or(std::path::Path::new(""));
+    let stem = format!(
+        "{}-annotated",
+        path.file_stem().unwrap_or_default().to_string_lossy()
+    );
+    let format = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(|extension| extension.parse::<OutputFormat>().ok())
+        .unwrap_or(OutputFormat::Png);
+    let mut image_buffer = image::open(source)
+        .map_err(std::io::Error::other)?
+        .to_rgba8();
+    crate::annotation::flatten(&mut image_buffer, annotations);
+    let target = dir.join(claim_free_filename(
+        dir,
+        None,
+        subdir,
+        &stem,
+        format.extension(),
+    )?);
+    if let Err(e) = save_image(&image_buffer, &target, format.into()) {
+        let _ = std::fs::remove_file(&target);
+        return Err(std::io::Error::other(e));
+    }
+    Ok(target)
+}
+
+// the versions of edited screenshots from before each edit, hidden from the scan
+const EDIT_BACKUP_DIR_NAME: &str = ".rayshot-originals";
+
+pub fn edit_backup_dir(config: &crate::config::RayshotConfig) -> std::path::PathBuf {
+    config.screenshot_dir.join(EDIT_BACKUP_DIR_NAME)
+}
+
+// copies the screenshot at `path` to the edit backup folder and rewrites it with `edits` applied,
+// in the format of its extension, returns the backup's name relative to that folder, the size
+// before the edit and the edited image
+pub fn edit_screenshot(
+    config: &crate::config::RayshotConfig,
+    path: &std::path::Path,
+    filename: &str,
+    edits: &[crate::edit::Edit],
+) -> std::io::Result<(String, (u32, u32), image::RgbaImage)> {
+    let relative_path = std::path::Path::new(filename);
+    let extension = relative_path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let format = extension
+        .parse::<OutputFormat>()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let image_buffer = image::open(path).map_err(std::io::Error::other)?.to_rgba8();
+    let edited = crate::edit::apply_all(&image_buffer, edits)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+
+    let backup_dir = edit_backup_dir(config);
+    let backup = claim_free_filename(
+        &backup_dir,
+        None,
+        relative_path.parent().unwrap_or(std::path::Path::new("")),
+        &relative_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy(),
+        &extension,
+    )?;
+    let backup_path = backup_dir.join(&backup);
+    if let Err(e) = std::fs::copy(path, &backup_path) {
+        remove_edit_backup(config, &backup);
+        return Err(e);
+    }
+    // saved next to the file and renamed over it, so a failed save leaves it untouched
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    let result = save_image(&edited, &temp_path, format.into())
+        .map_err(std::io::Error::other)
+        .and_then(|()| std::fs::rename(&temp_path, path));
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        remove_edit_backup(config, &backup);
+        return Err(e);
+    }
+    Ok((backup, image_buffer.dimensions(), edited))
+}
+
+// exchanges the screenshot at `path` with its edit backup, which then holds the other version
+pub fn swap_edit_backup(
+    config: &crate::config::RayshotConfig,
+    path: &std::path::Path,
+    backup: &str,
+) -> std::io::Result<()> {
+    let backup_path = edit_backup_dir(config).join(backup);
+    if !backup_path.is_file() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("'{}' is gone", backup_path.display()),
+        ));
+    }
+    let mut temp_name = backup_path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".swap");
+    let temp_path = backup_path.with_file_name(temp_name);
+    crate::trash::move_file(path, &temp_path)?;
+    if let Err(e) = crate::trash::move_file(&backup_path, path) {
+        let _ = crate::trash::move_file(&temp_path, path);
+        return Err(e);
+    }
+    crate::trash::move_file(&temp_path, &backup_path)
+}
+
+// best effort, for backups no journal step refers to anymore
+pub fn remove_edit_backup(config: &crate::config::RayshotConfig, backup: &str) {
+    let backup_dir = edit_backup_dir(config);
+    let backup_path = backup_dir.join(backup);
+    let _ = std::fs::remove_file(&backup_path);
+    remove_empty_parents(&backup_dir, &backup_path);
+}
+
+// saves a local screenshot again with `encoder` next to the original, which is left in place,
+// and returns the new filename relative to `screenshot_dir`
+pub fn reencode_screenshot(
+    config: &crate::config::RayshotConfig,
+    filename: &str,
+    encoder: EncoderConfig,
+) -> std::io::Result<String> {
+    let path = std::path::Path::new(filename);
+    let current_format = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(|extension| extension.parse::<OutputFormat>().ok());
+    if current_format == Some(encoder.format()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "'{}' is already saved as {}",
+                filename,
+                encoder.format().extension()
+            ),
+        ));
+    }
+    let subdir = path.parent().unwrap_or(std::path::Path::new(""));
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let trash_dir = (config.trash_backend == crate::config::TrashBackend::Folder)
+        .then_some(config.trash_dir.as_path());
+    let reencoded = claim_free_filename(
+        &config.screenshot_dir,
+        trash_dir,
+        subdir,
+        &stem,
+        encoder.format().extension(),
+    )?;
+    let target = config.screenshot_dir.join(&reencoded);
+    let result = image::open(config.screenshot_dir.join(filename))
+        .and_then(|image| save_image(&image.to_rgba8(), &target, encoder));
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&target);
+        return Err(std::io::Error::other(e));
+    }
+    Ok(reencoded)
+}
+
+#[derive(Debug, Clone)]
+pub enum BulkAction {
+    Trash,
+    Restore,
+    // plain copies of the files
+    CopyTo(std::path::PathBuf),
+    // decoded and saved again in another format, the originals stay
+    ExportTo(std::path::PathBuf, OutputFormat),
+    // saved again in another format next to the original, which is moved to the trash
+    Reencode(OutputFormat),
+}
+
+impl BulkAction {
+    pub fn label(&self) -> String {
+        match self {
+            BulkAction::Trash => "Trashing".to_string(),
+            BulkAction::Restore => "Restoring".to_string(),
+            BulkAction::CopyTo(dir) => format!("Copying to '{}'", dir.display()),
+            BulkAction::ExportTo(dir, format) => {
+                format!("Exporting {} to '{}'", format.extension(), dir.display())
+            }
+            BulkAction::Reencode(format) => format!("Re-encoding as {}", format.extension()),
+        }
+    }
+
+    fn applies_to(&self, file_location: crate::FileLocation) -> bool {
+        match self {
+            BulkAction::Trash | BulkAction::Reencode(_) => {
+                matches!(file_location, crate::FileLocation::Local)
+            }
+            BulkAction::Restore => matches!(file_location, crate::FileLocation::Trash),
+            BulkAction::CopyTo(_) | BulkAction::ExportTo(..) => true,
+        }
+    }
+}
+
+// runs `action` on every entry it applies to at once, each waiting for its own `file_lock`,
+// the moves are undone as one step
+pub async fn run_bulk_action(
+    rayshot_state: crate::RayshotState,
+    egui_ctx: eframe::egui::Context,
+    entries: Vec<crate::ScreenshotEntry>,
+    action: BulkAction,
+) {
+    let mut targets = Vec::new();
+    for entry in entries {
+        if action.applies_to(*entry.file_location.lock().await) {
+            targets.push(entry);
+        }
+    }
+    if targets.is_empty() {
+        return;
+    }
+    {
+        let mut bulk_progress = rayshot_state.bulk_progress.lock().await;
+        if bulk_progress.is_some() {
+            drop(bulk_progress);
+            return crate::state::report_error(
+                &rayshot_state,
+                &egui_ctx,
+                "Another bulk action is still running".to_string(),
+            )
+            .await;
+        }
+        *bulk_progress = Some(crate::state::BulkProgress {
+            label: action.label(),
+            done: 0,
+            total: targets.len(),
+        });
+    }
+    egui_ctx.request_repaint();
+
+    let mut tasks = tokio::task::JoinSet::new();
+    for entry in targets {
+        let rayshot_state = rayshot_state.clone();
+        let egui_ctx = egui_ctx.clone();
+        let action = action.clone();
+        tasks.spawn(async move { bulk_entry(&rayshot_state, &egui_ctx, &entry, &action).await });
+    }
+    let mut operations = Vec::new();
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok(entry_operations) => operations.extend(entry_operations),
+            Err(e) => {
+                crate::state::report_error(
+                    &rayshot_state,
+                    &egui_ctx,
+                    format!("Bulk action task failed: {}", e),
+                )
+                .await;
+            }
+        }
+        if let Some(bulk_progress) = rayshot_state.bulk_progress.lock().await.as_mut() {
+            bulk_progress.done += 1;
+        }
+        egui_ctx.request_repaint();
+    }
+
+    crate::metadata::save(&rayshot_state, &egui_ctx).await;
+    crate::journal::record(&rayshot_state, &egui_ctx, operations).await;
+    *rayshot_state.bulk_progress.lock().await = None;
+    rayshot_state.manage_texture_cache(&egui_ctx).await;
+    egui_ctx.request_repaint();
+}
+
+async fn bulk_entry(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+    action: &BulkAction,
+) -> Vec<crate::journal::FileOperation> {
+    match action {
+        BulkAction::Trash | BulkAction::Restore => move_entry(rayshot_state, egui_ctx, entry)
+            .await
+            .into_iter()
+            .collect(),
+        BulkAction::CopyTo(dir) => {
+            export_entry(rayshot_state, egui_ctx, entry, dir.clone(), None).await;
+            Vec::new()
+        }
+        BulkAction::ExportTo(dir, format) => {
+            export_entry(
+                rayshot_state,
+                egui_ctx,
+                entry,
+                dir.clone(),
+                Some((*format).into()),
+            )
+            .await;
+            Vec::new()
+        }
+        BulkAction::Reencode(format) => {
+            reencode_entry(rayshot_state, egui_ctx, entry, (*format).into()).await
+        }
+    }
+}
+
+async fn export_entry(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+    dir: std::path::PathBuf,
+    encoder: Option<EncoderConfig>,
+) {
+    let result = {
+        let _file_lock = entry.file_lock.lock().await;
+        let file_location = *entry.file_location.lock().await;
+        let config = rayshot_state.config.clone();
+        let trash = rayshot_state.freedesktop_trash.clone();
+        let filename = entry.filename.clone();
+        tokio::task::spawn_blocking(move || {
+            let source = file_location.file_path(&config, trash.as_ref(), &filename)?;
+            export_screenshot(&source, &filename, &dir, encoder)
+        })
+        .await
+    };
+    let error_msg = match result {
+        Ok(Ok(_)) => return,
+        Ok(Err(e)) => format!("Failed to export '{}': {}", entry.filename, e),
+        Err(e) => format!("Export task failed: {}", e),
+    };
+    crate::state::report_error(rayshot_state, egui_ctx, error_msg).await;
+}
+
+// the re-encoded file joins the gallery right after the original, which goes to the trash so
+// undo can bring it back
+async fn reencode_entry(
+    rayshot_state: &crate::RayshotState,
+    egui_ctx: &eframe::egui::Context,
+    entry: &crate::ScreenshotEntry,
+    encoder: EncoderConfig,
+) -> Vec<crate::journal::FileOperation> {
+    let result = {
+        let _file_lock = entry.file_lock.lock().await;
+        let config = rayshot_state.config.clone();
+        let filename = entry.filename.clone();
+        tokio::task::spawn_blocking(move || {
+            let reencoded = reencode_screenshot(&config, &filename, encoder)?;
+            let file_size = std::fs::metadata(config.screenshot_dir.join(&reencoded))?.len();
+            Ok::<_, std::io::Error>((reencoded, file_size))
+        })
+        .await
+    };
+    let (reencoded, file_size) = match result {
+        Ok(Ok(reencoded)) => reencoded,
+        Ok(Err(e)) => {
+            crate::state::report_error(
+                rayshot_state,
+                egui_ctx,
+                format!("Failed to re-encode '{}': {}", entry.filename, e),
+            )
+            .await;
+            return Vec::new();
+        }
+        Err(e) => {
+            crate::state::report_error(
+                rayshot_state,
+                egui_ctx,
+                format!("Re-encode task failed: {}", e),
+            )
+            .await;
+            return Vec::new();
+        }
+    };
+
+    // tags, notes and where it came from carry over, the pixels may have changed
+    crate::metadata::change(rayshot_state, |metadata| {
+        let mut reencoded_metadata = metadata
+            .get(entry.filename.as_str())
+            .cloned()
+            .unwrap_or_default();
+        reencoded_metadata.set_location(crate::FileLocation::Local);
+        reencoded_metadata.file_size = Some(file_size);
+        reencoded_metadata.hash = None;
+        *metadata.entry(&reencoded) = reencoded_metadata;
+    })
+    .await;
+    let mut reencoded_entry =
+        crate::ScreenshotEntry::new(std::sync::Arc::new(reencoded), crate::FileLocation::Local);
+    reencoded_entry.burst_id = entry.burst_id;
+    *reencoded_entry.file_size.lock().await = Some(file_size as usize);
+    rayshot_state
+        .insert_entry_after(entry, reencoded_entry)
+        .await;
+    move_entry(rayshot_state, egui_ctx, entry)
+        .await
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    // trashed files from newest to oldest as (name, days since trashed, size)
+    const TRASHED: [(&str, u64, usize); 5] = [
+        ("a.png", 1, 10),
+        ("b.png", 2, 10),
+        ("c.png", 3, 50),
+        ("d.png", 4, 5),
+        ("e.png", 40, 1),
+    ];
+
+    const DAY: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+    // the names `retention` deletes from a fresh trash folder holding `TRASHED` and a note that
+    // is no screenshot, the index knows the trash times in days of `recorded`
+    fn purge_fresh_trash(
+        test_name: &str,
+        retention: crate::config::TrashRetention,
+        recorded: &[(&str, u64)],
+    ) -> Vec<String> {
+        let root =
+            std::env::temp_dir().join(format!("rayshot-test-{}-{}", test_name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let config = crate::config::RayshotConfig {
+            screenshot_dir: root.join("screenshots"),
+            trash_dir: root.join("trashed"),
+            ..Default::default()
+        };
+        std::fs::create_dir_all(&config.trash_dir).unwrap();
+        let now = std::time::SystemTime::now();
+        let files = TRASHED.into_iter().chain([("notes.txt", 100, 1)]);
+        for (name, days, size) in files {
+            let path = config.trash_dir.join(name);
+            std::fs::write(&path, vec![0; size]).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(now - DAY * days as u32)
+                .unwrap();
+        }
+        let trash_times = recorded
+            .iter()
+            .map(|(name, days)| {
+                let trashed_at = chrono::Local::now() - DAY * *days as u32;
+                (name.to_string(), trashed_at.naive_local())
+            })
+            .collect();
+
+        let purge = super::purge_trash(&config, &retention, &trash_times).unwrap();
+        assert!(purge.errors.is_empty(), "{:?}", purge.errors);
+        let mut left: Vec<_> = std::fs::read_dir(&config.trash_dir)
+            .unwrap()
+            .map(|dir_entry| dir_entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let mut purged = purge.purged;
+        purged.sort();
+        assert!(left.iter().any(|name| name == "notes.txt"));
+        assert_eq!(left.len() + purged.len(), TRASHED.len() + 1);
+        purged
+    }
+
+    #[test]
+    fn purge_trash_deletes_everything_older_than_the_first_file_past_a_limit() {
+        let retention = |max_age_days, max_total_bytes, max_count| crate::config::TrashRetention {
+            max_age_days,
+            max_total_bytes,
+            max_count,
+            ..Default::default()
+        };
+        assert_eq!(
+            purge_fresh_trash("age", retention(Some(30), None, None), &[]),
+            ["e.png"]
+        );
+        assert_eq!(
+            purge_fresh_trash("count", retention(None, None, Some(2)), &[]),
+            ["c.png", "d.png", "e.png"]
+        );
+        // the small older files are not kept in place of the big newer one
+        assert_eq!(
+            purge_fresh_trash("bytes", retention(None, Some(25), None), &[]),
+            ["c.png", "d.png", "e.png"]
+        );
+        // only kept files count towards `max_count` and `max_total_bytes`
+        assert_eq!(
+            purge_fresh_trash("combined", retention(Some(30), Some(25), Some(4)), &[]),
+            ["c.png", "d.png", "e.png"]
+        );
+        assert_eq!(
+            purge_fresh_trash("all", retention(Some(30), Some(70), Some(3)), &[]),
+            ["d.png", "e.png"]
+        );
+        assert_eq!(
+            purge_fresh_trash("empty", retention(None, None, Some(0)), &[]),
+            ["a.png", "b.png", "c.png", "d.png", "e.png"]
+        );
+    }
+
+    #[test]
+    fn purge_trash_counts_ages_from_the_recorded_trash_time() {
+        let retention = crate::config::TrashRetention {
+            max_age_days: Some(30),
+            ..Default::default()
+        };
+        // trashed yesterday although the file itself is old, and the other way around
+        assert_eq!(
+            purge_fresh_trash("recorded", retention, &[("e.png", 1), ("a.png", 31)]),
+            ["a.png"]
+        );
+    }
+
+    #[test]
+    fn deleting_a_file_no_longer_in_the_trash_purges_nothing() {
+        let config = crate::config::RayshotConfig {
+            trash_dir: std::env::temp_dir()
+                .join(format!("rayshot-test-gone-{}", std::process::id())),
+            ..Default::default()
+        };
+        let mut purge = super::TrashPurge::default();
+        let restored = super::TrashedFile {
+            relative_path: "restored.png".into(),
+            trashed_at: std::time::SystemTime::now(),
+            size: 0,
+            freedesktop_item: None,
+        };
+        purge.delete(&config, &restored);
+        assert!(purge.purged.is_empty());
+        assert!(purge.errors.is_empty(), "{:?}", purge.errors);
+    }
+}
@@ -0,0 +1,396 @@
+// the home trash of the freedesktop.org Trash specification, shared with file managers
+// https://specifications.freedesktop.org/trash-spec/latest/
+
+const TRASH_INFO_EXTENSION: &str = "trashinfo";
+const DELETION_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+#[derive(Debug, Clone)]
+pub struct TrashItem {
+    // the file name inside `files/`, `info/` holds `<name>.trashinfo`
+    pub name: String,
+    pub original_path: std::path::PathBuf,
+    pub deleted_at: chrono::NaiveDateTime,
+}
+
+// clones share the remembered item names, so one handle kept for the session looks trashed
+// screenshots up without reading every info file each time
+#[derive(Clone)]
+pub struct FreedesktopTrash {
+    root: std::path::PathBuf,
+    // original paths and the names of their newest items in `files/`, refilled by `items`
+    item_names:
+        std::sync::Arc<std::sync::Mutex<std::collections::BTreeMap<std::path::PathBuf, String>>>,
+}
+
+impl FreedesktopTrash {
+    // `$XDG_DATA_HOME/Trash`, falling back to `~/.local/share/Trash`
+    pub fn home() -> std::io::Result<Self> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(std::path::PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME")
+                    .map(|home| std::path::Path::new(&home).join(".local").join("share"))
+            })
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "Neither XDG_DATA_HOME nor HOME is set, cannot locate the trash",
+                )
+            })?;
+        Ok(Self {
+            root: data_home.join("Trash"),
+            item_names: Default::default(),
+        })
+    }
+
+    // `shared` when the caller keeps a handle for the session, a fresh one otherwise
+    pub fn shared_or_home(shared: Option<&Self>) -> std::io::Result<Self> {
+        match shared {
+            Some(trash) => Ok(trash.clone()),
+            None => Self::home(),
+        }
+    }
+
+    fn item_names(
+        &self,
+    ) -> std::sync::MutexGuard<'_, std::collections::BTreeMap<std::path::PathBuf, String>> {
+        self.item_names.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn files_dir(&self) -> std::path::PathBuf {
+        self.root.join("files")
+    }
+
+    fn info_dir(&self) -> std::path::PathBuf {
+        self.root.join("info")
+    }
+
+    pub fn file_path(&self, item: &TrashItem) -> std::path::PathBuf {
+        self.files_dir().join(&item.name)
+    }
+
+    fn info_path(&self, name: &str) -> std::path::PathBuf {
+        self.info_dir()
+            .join(format!("{}.{}", name, TRASH_INFO_EXTENSION))
+    }
+
+    pub fn trash(&self, path: &std::path::Path) -> std::io::Result<()> {
+        let original_path = std::path::absolute(path)?;
+        let file_name = original_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("'{}' has no file name", path.display()),
+                )
+            })?;
+        std::fs::create_dir_all(self.files_dir())?;
+        std::fs::create_dir_all(self.info_dir())?;
+
+        // the info file is created first and exclusively, it reserves the name in `files/`
+        let trash_info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            percent_encode(&original_path),
+            chrono::Local::now().format(DELETION_DATE_FORMAT)
+        );
+        let (stem, extension) = match file_name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+            _ => (file_name.as_str(), None),
+        };
+        let mut attempt = 1;
+        let name = loop {
+            let name = match (attempt, extension) {
+                (1, _) => file_name.clone(),
+                (_, Some(extension)) => format!("{}-{}.{}", stem, attempt, extension),
+                (_, None) => format!("{}-{}", stem, attempt),
+            };
+            attempt += 1;
+            if self.files_dir().join(&name).exists() {
+                continue;
+            }
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(self.info_path(&name))
+            {
+                Ok(mut info_file) => {
+                    std::io::Write::write_all(&mut info_file, trash_info.as_bytes())?;
+                    break name;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        };
+
+        if let Err(e) = move_file(&original_path, &self.files_dir().join(&name)) {
+            let _ = std::fs::remove_file(self.info_path(&name));
+            return Err(e);
+        }
+        self.item_names().insert(original_path, name);
+        Ok(())
+    }
+
+    // every item with a readable info file, unreadable ones belong to someone else to fix, the
+    // newest item of each original path is remembered for `find`
+    pub fn items(&self) -> std::io::Result<Vec<TrashItem>> {
+        let dir_entries = match std::fs::read_dir(self.info_dir()) {
+            Ok(dir_entries) => dir_entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut items = Vec::new();
+        for dir_entry in dir_entries {
+            let path = dir_entry?.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(&format!(".{}", TRASH_INFO_EXTENSION)))
+            else {
+                continue;
+            };
+            if let Some(item) = self.read_item(name) {
+                items.push(item);
+            }
+        }
+        let mut newest = std::collections::BTreeMap::new();
+        for item in &items {
+            newest
+                .entry(&item.original_path)
+                .and_modify(|newest: &mut &TrashItem| {
+                    if newest.deleted_at < item.deleted_at {
+                        *newest = item;
+                    }
+                })
+                .or_insert(item);
+        }
+        *self.item_names() = newest
+            .into_iter()
+            .map(|(original_path, item)| (original_path.clone(), item.name.clone()))
+            .collect();
+        Ok(items)
+    }
+
+    // the most recently trashed item that came from `original_path`
+    pub fn find(&self, original_path: &std::path::Path) -> std::io::Result<Option<TrashItem>> {
+        let original_path = std::path::absolute(original_path)?;
+        let cached_name = self.item_names().get(&original_path).cloned();
+        // a remembered name is trusted while its info file still points back to the path
+        if let Some(item) = cached_name
+            .and_then(|name| self.read_item(&name))
+            .filter(|item| item.original_path == original_path)
+        {
+            return Ok(Some(item));
+        }
+        Ok(self
+            .items()?
+            .into_iter()
+            .filter(|item| item.original_path == original_path)
+            .max_by_key(|item| item.deleted_at))
+    }
+
+    fn read_item(&self, name: &str) -> Option<TrashItem> {
+        let content = std::fs::read_to_string(self.info_path(name)).ok()?;
+        parse_trash_info(name, &content)
+    }
+
+    // never overwrites a file that took the original place in the meantime
+    pub fn restore(&self, original_path: &std::path::Path) -> std::io::Result<()> {
+        let Some(item) = self.find(original_path)? else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("'{}' is not in the trash", original_path.display()),
+            ));
+        };
+        if item.original_path.exists() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("'{}' already exists", item.original_path.display()),
+            ));
+        }
+        if let Some(parent) = item.original_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        move_file(&self.file_path(&item), &item.original_path)?;
+        self.forget(&item);
+        std::fs::remove_file(self.info_path(&item.name))
+    }
+
+    // an older item trashed from the same path is found again by the next `items`
+    fn forget(&self, item: &TrashItem) {
+        let mut item_names = self.item_names();
+        if item_names.get(&item.original_path) == Some(&item.name) {
+            item_names.remove(&item.original_path);
+        }
+    }
+
+    // `false` when the file was already gone, e.g. restored meanwhile, a leftover info file is
+    // removed all the same
+    pub fn delete(&self, item: &TrashItem) -> std::io::Result<bool> {
+        self.forget(item);
+        let deleted = match std::fs::remove_file(self.file_path(item)) {
+            Ok(()) => true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => return Err(e),
+        };
+        match std::fs::remove_file(self.info_path(&item.name)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(deleted),
+        }
+    }
+}
+
+// the trash may live on another filesystem than the screenshots
+pub fn move_file(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+            std::fs::copy(from, to)?;
+            std::fs::remove_file(from)
+        }
+        result => result,
+    }
+}
+
+fn parse_trash_info(name: &str, content: &str) -> Option<TrashItem> {
+    let mut lines = content.lines().map(str::trim);
+    if lines.next()? != "[Trash Info]" {
+        return None;
+    }
+    let mut original_path = None;
+    let mut deleted_at = None;
+    for line in lines {
+        if line.starts_with('[') {
+            break;
+        }
+        match line.split_once('=') {
+            Some(("Path", path)) => original_path = Some(percent_decode(path)?),
+            Some(("DeletionDate", date)) => {
+                deleted_at = chrono::NaiveDateTime::parse_from_str(date, DELETION_DATE_FORMAT).ok();
+            }
+            _ => {}
+        }
+    }
+    Some(TrashItem {
+        name: name.to_string(),
+        original_path: original_path?,
+        deleted_at: deleted_at?,
+    })
+}
+
+fn percent_encode(path: &std::path::Path) -> String {
+    let mut encoded = String::new();
+    for byte in path.as_os_str().as_encoded_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(*byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(encoded: &str) -> Option<std::path::PathBuf> {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    Some(std::path::PathBuf::from(String::from_utf8(bytes).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn paths_survive_the_percent_encoding_round_trip() {
+        for path in [
+            "/home/me/a.png",
+            "/home/me/Shot #1 100%.png",
+            "/原神/a b/c.webp",
+        ] {
+            let encoded = super::percent_encode(std::path::Path::new(path));
+            assert!(encoded.is_ascii() && !encoded.contains(' '), "{}", encoded);
+            assert_eq!(
+                super::percent_decode(&encoded),
+                Some(std::path::PathBuf::from(path))
+            );
+        }
+        assert_eq!(
+            super::percent_encode(std::path::Path::new("/a b%.png")),
+            "/a%20b%25.png"
+        );
+        for broken in ["/a%2", "/a%zz.png", "/%FF.png"] {
+            assert_eq!(super::percent_decode(broken), None, "{}", broken);
+        }
+    }
+
+    #[test]
+    fn parse_trash_info_reads_the_trash_info_group() {
+        let item = super::parse_trash_info(
+            "a-2.png",
+            "[Trash Info]\nPath=/home/me/a%20b.png\nDeletionDate=2024-05-06T07:08:09\n[Other]\nPath=/x\n",
+        )
+        .unwrap();
+        assert_eq!(item.name, "a-2.png");
+        assert_eq!(item.original_path, std::path::Path::new("/home/me/a b.png"));
+        assert_eq!(item.deleted_at.to_string(), "2024-05-06 07:08:09",);
+        for broken in [
+            "Path=/a.png\nDeletionDate=2024-05-06T07:08:09\n",
+            "[Trash Info]\nDeletionDate=2024-05-06T07:08:09\n",
+            "[Trash Info]\nPath=/a.png\n",
+            "[Trash Info]\nPath=/a.png\nDeletionDate=yesterday\n",
+            "[Trash Info]\nPath=/a%2.png\nDeletionDate=2024-05-06T07:08:09\n",
+            "[Trash Info]\n[Other]\nPath=/a.png\nDeletionDate=2024-05-06T07:08:09\n",
+        ] {
+            assert!(
+                super::parse_trash_info("a.png", broken).is_none(),
+                "{}",
+                broken
+            );
+        }
+    }
+
+    #[test]
+    fn clones_share_where_trashed_files_went() {
+        let root = std::env::temp_dir().join(format!("rayshot-test-trash-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let trash = super::FreedesktopTrash {
+            root: root.join("Trash"),
+            item_names: Default::default(),
+        };
+        let path = root.join("shots").join("a.png");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "first").unwrap();
+        trash.trash(&path).unwrap();
+        std::fs::write(&path, "second").unwrap();
+        trash.clone().trash(&path).unwrap();
+
+        let newest = trash.find(&path).unwrap().unwrap();
+        assert_eq!(newest.name, "a-2.png");
+        trash.restore(&path).unwrap();
+        let restored = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // a fresh handle reads the info files to find what is left
+        let fresh = super::FreedesktopTrash {
+            root: root.join("Trash"),
+            item_names: Default::default(),
+        };
+        let left = fresh.find(&path).unwrap().unwrap();
+        let deleted = fresh.delete(&left).unwrap();
+        let gone = trash.find(&path).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(restored, "second");
+        assert_eq!(left.name, "a.png");
+        assert!(deleted);
+        assert!(gone.is_none());
+    }
+}
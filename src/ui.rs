@@ -0,0 +1,2389 @@
+// UI sizing constants
+const THUMBNAIL_SIZE: f32 = 400.0;
+const MAIN_IMAGE_WIDTH_RATIO: f32 = 1.0;
+const MAIN_IMAGE_HEIGHT_RATIO: f32 = 1.0;
+const HORIZONTAL_LIST_HEIGHT: f32 = 200.0;
+const LOADING_PLACEHOLDER_SIZE: f32 = 200.0;
+
+// UI spacing constants
+const WELCOME_SECTION_TOP_SPACING: f32 = 50.0;
+const WELCOME_SECTION_MIDDLE_SPACING: f32 = 20.0;
+const WELCOME_SECTION_BOTTOM_SPACING: f32 = 10.0;
+const SCREENSHOT_INFO_SPACING: f32 = 10.0;
+const THUMBNAIL_SPACING: f32 = 10.0;
+const SECTION_SEPARATOR_SPACING: f32 = 20.0;
+const ERROR_LIST_ITEM_SPACING: f32 = 5.0;
+
+// Bulk action constants
+const SELECTION_FILTER_WIDTH: f32 = 160.0;
+const BULK_DIR_WIDTH: f32 = 200.0;
+const BULK_PROGRESS_WIDTH: f32 = 240.0;
+
+// Filter bar constants
+const FILTER_TEXT_WIDTH: f32 = 120.0;
+const FILTER_DATE_WIDTH: f32 = 90.0;
+const FILTER_SIZE_WIDTH: f32 = 50.0;
+
+// Annotation editor constants
+const ANNOTATION_MAX_SIZE: f32 = 96.0;
+const ANNOTATION_TEXT_WIDTH: f32 = 160.0;
+
+// Metadata editor constants
+const TAG_INPUT_WIDTH: f32 = 120.0;
+const NOTES_WIDTH: f32 = 240.0;
+const NOTES_ROWS: usize = 3;
+const RENAME_INPUT_WIDTH: f32 = 240.0;
+
+// Error window constants
+const ERROR_WINDOW_DEFAULT_WIDTH: f32 = 400.0;
+
+// Region overlay constants
+const REGION_OVERLAY_DIM_ALPHA: u8 = 160;
+const REGION_OVERLAY_FONT_SIZE: f32 = 16.0;
+const REGION_OVERLAY_HINT_MARGIN: f32 = 12.0;
+const REGION_SELECTION_STROKE_WIDTH: f32 = 2.0;
+const MAGNIFIER_SIZE: f32 = 160.0;
+const MAGNIFIER_ZOOM: f32 = 8.0;
+const MAGNIFIER_CURSOR_OFFSET: f32 = 24.0;
+
+// the frame of a pending region selection, shown fullscreen until it is answered
+struct RegionOverlay {
+    image: std::sync::Arc<image::RgbaImage>,
+    texture: eframe::egui::TextureHandle,
+    // in image pixels
+    drag_start: Option<eframe::egui::Pos2>,
+    drag_end: Option<eframe::egui::Pos2>,
+    result: Option<Option<(u32, u32, u32, u32)>>,
+}
+
+// the filter bar's fields, `filter` is what the text in them parses to
+#[derive(Default)]
+struct FilterBar {
+    filter: crate::filter::GalleryFilter,
+    captured_from: String,
+    captured_to: String,
+    min_width: String,
+    min_height: String,
+}
+
+// the main image's annotation mode, the layer itself lives in the current entry's metadata
+struct AnnotationEditor {
+    tool: crate::annotation::Tool,
+    color: eframe::egui::Color32,
+    size: f32,
+    // placed by the text tool
+    text: String,
+    // image pixels of the shape being dragged
+    drag: Vec<[f32; 2]>,
+    // the current entry with its pixelate and blur regions applied, rendered in the background
+    effects_preview: std::sync::Arc<tokio::sync::Mutex<Option<EffectsPreview>>>,
+    preview_requested: Option<(String, Vec<crate::annotation::Annotation>)>,
+    preview_generation: u64,
+    // where the last annotated copy was exported to
+    exported: std::sync::Arc<tokio::sync::Mutex<Option<std::path::PathBuf>>>,
+}
+
+impl AnnotationEditor {
+    fn new() -> Self {
+        let tool = crate::annotation::Tool::Arrow;
+        Self {
+            tool,
+            color: eframe::egui::Color32::RED,
+            size: tool.default_size(),
+            text: String::new(),
+            drag: Vec::new(),
+            effects_preview: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+            preview_requested: None,
+            preview_generation: 0,
+            exported: std::sync::Arc::new(tokio::sync::Mutex::new(None)),
+        }
+    }
+}
+
+struct EffectsPreview {
+    // previews of older requests never replace newer ones
+    generation: u64,
+    filename: String,
+    effects: Vec<crate::annotation::Annotation>,
+    texture: eframe::egui::TextureHandle,
+}
+
+// notes being typed, written to the index when the editor loses focus or another entry is shown
+struct NotesDraft {
+    filename: String,
+    text: String,
+    dirty: bool,
+}
+
+// edits queued for one entry, applied to its file together as one undo step
+#[derive(Default)]
+struct ImageEditor {
+    filename: String,
+    edits: Vec<crate::edit::Edit>,
+    // x, y, width and height
+    crop: [String; 4],
+    // width and height
+    resize: [String; 2],
+    filter: crate::edit::ResizeFilter,
+}
+
+// the new name being typed for an entry, without its folder and extension
+struct RenameInput {
+    filename: String,
+    stem: String,
+}
+
+pub struct RayshotApp {
+    rayshot_state: crate::RayshotState,
+    region_overlay: Option<RegionOverlay>,
+    confirm_empty_trash: bool,
+    // filenames of the entries bulk actions apply to, independent of the current entry
+    selection: std::collections::HashSet<String>,
+    // where shift-click ranges start
+    selection_anchor: Option<usize>,
+    selection_filter: String,
+    bulk_dir: String,
+    bulk_format: crate::storage::OutputFormat,
+    tag_input: String,
+    notes_draft: Option<NotesDraft>,
+    filter_bar: FilterBar,
+    annotation_editor: Option<AnnotationEditor>,
+    image_editor: Option<ImageEditor>,
+    rename_input: Option<RenameInput>,
+    // keeping a burst frame, undo and redo, handled here instead of as global hotkeys
+    window_shortcuts: Vec<(eframe::egui::KeyboardShortcut, crate::RayshotHotkey)>,
+}
+
+impl RayshotApp {
+    pub fn new(rayshot_state: crate::RayshotState) -> Self {
+        Self {
+            region_overlay: None,
+            confirm_empty_trash: false,
+            selection: std::collections::HashSet::new(),
+            selection_anchor: None,
+            selection_filter: String::new(),
+            bulk_dir: rayshot_state.config.export_dir.display().to_string(),
+            bulk_format: crate::storage::OutputFormat::Png,
+            tag_input: String::new(),
+            notes_draft: None,
+            filter_bar: FilterBar::default(),
+            annotation_editor: None,
+            image_editor: None,
+            rename_input: None,
+            // validated with the config
+            window_shortcuts: rayshot_state.config.window_shortcuts().unwrap_or_default(),
+            rayshot_state,
+        }
+    }
+
+    fn profile_switcher(&self, ui: &mut eframe::egui::Ui, id_salt: &str) {
+        let profiles = &self.rayshot_state.config.profiles;
+        let Ok(mut active_profile_idx) = self.rayshot_state.active_profile_idx.try_lock() else {
+            ui.label("Loading...");
+            return;
+        };
+        eframe::egui::ComboBox::from_id_salt(id_salt)
+            .selected_text(
+                profiles
+                    .get(*active_profile_idx)
+                    .map_or("", |profile| profile.name.as_str()),
+            )
+            .show_ui(ui, |ui| {
+                for (idx, profile) in profiles.iter().enumerate() {
+                    let label = match &profile.hotkey {
+                        Some(hotkey) => format!("{} ({})", profile.name, hotkey),
+                        None => profile.name.clone(),
+                    };
+                    ui.selectable_value(&mut *active_profile_idx, idx, label);
+                }
+            });
+    }
+
+    // returns whether the region overlay took over the frame
+    fn region_overlay(&mut self, ctx: &eframe::egui::Context) -> bool {
+        if self.region_overlay.is_none() {
+            let Ok(region_selection) = self.rayshot_state.region_selection.try_lock() else {
+                return false;
+            };
+            let Some(request) = region_selection.as_ref() else {
+                return false;
+            };
+            let image = request.image.clone();
+            let image_size = [image.width() as usize, image.height() as usize];
+            let texture = ctx.load_texture(
+                "region_selection",
+                eframe::epaint::ColorImage::from_rgba_unmultiplied(image_size, image.as_raw()),
+                eframe::egui::TextureOptions::NEAREST,
+            );
+            self.region_overlay = Some(RegionOverlay {
+                image,
+                texture,
+                drag_start: None,
+                drag_end: None,
+                result: None,
+            });
+            ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Fullscreen(true));
+        }
+        let Some(overlay) = &mut self.region_overlay else {
+            return false;
+        };
+
+        if overlay.result.is_none() && ctx.input(|i| i.key_pressed(eframe::egui::Key::Escape)) {
+            overlay.result = Some(None);
+        }
+
+        eframe::egui::CentralPanel::default()
+            .frame(eframe::egui::Frame::NONE.fill(eframe::egui::Color32::BLACK))
+            .show(ctx, |ui| {
+                let image_size =
+                    eframe::egui::vec2(overlay.image.width() as f32, overlay.image.height() as f32);
+                let screen_rect = ui.max_rect();
+                // never upscale so one screen pixel stays at most one image pixel
+                let scale = (screen_rect.width() / image_size.x)
+                    .min(screen_rect.height() / image_size.y)
+                    .min(1.0);
+                let image_rect =
+                    eframe::egui::Rect::from_center_size(screen_rect.center(), image_size * scale);
+                let to_image = |pos: eframe::egui::Pos2| {
+                    ((pos - image_rect.min) / scale)
+                        .to_pos2()
+                        .clamp(eframe::egui::Pos2::ZERO, image_size.to_pos2())
+                };
+                let to_screen = |pos: eframe::egui::Pos2| image_rect.min + pos.to_vec2() * scale;
+
+                let response = ui.allocate_rect(screen_rect, eframe::egui::Sense::drag());
+                if overlay.result.is_none() {
+                    if let Some(pos) = response.interact_pointer_pos() {
+                        if response.drag_started() {
+                            overlay.drag_start = Some(to_image(pos).floor());
+                        }
+                        overlay.drag_end = Some(to_image(pos).round());
+                    }
+                }
+                let selection = overlay
+                    .drag_start
+                    .zip(overlay.drag_end)
+                    .map(|(start, end)| eframe::egui::Rect::from_two_pos(start, end));
+                if response.drag_stopped() && overlay.result.is_none() {
+                    match selection.filter(|s| s.width() >= 1.0 && s.height() >= 1.0) {
+                        Some(s) => {
+                            overlay.result = Some(Some((
+                                s.min.x as u32,
+                                s.min.y as u32,
+                                s.width() as u32,
+                                s.height() as u32,
+                            )))
+                        }
+                        // a plain click selects nothing, start over
+                        None => {
+                            overlay.drag_start = None;
+                            overlay.drag_end = None;
+                        }
+                    }
+                }
+
+                let painter = ui.painter();
+                let full_uv = eframe::egui::Rect::from_min_max(
+                    eframe::egui::pos2(0.0, 0.0),
+                    eframe::egui::pos2(1.0, 1.0),
+                );
+                painter.image(
+                    overlay.texture.id(),
+                    image_rect,
+                    full_uv,
+                    eframe::egui::Color32::WHITE,
+                );
+
+                // dim everything outside the selection
+                let dim = eframe::egui::Color32::from_black_alpha(REGION_OVERLAY_DIM_ALPHA);
+                match selection
+                    .map(|s| eframe::egui::Rect::from_min_max(to_screen(s.min), to_screen(s.max)))
+                {
+                    Some(selected) => {
+                        for outside in [
+                            eframe::egui::Rect::from_x_y_ranges(
+                                image_rect.x_range(),
+                                image_rect.top()..=selected.top(),
+                            ),
+                            eframe::egui::Rect::from_x_y_ranges(
+                                image_rect.x_range(),
+                                selected.bottom()..=image_rect.bottom(),
+                            ),
+                            eframe::egui::Rect::from_x_y_ranges(
+                                image_rect.left()..=selected.left(),
+                                selected.y_range(),
+                            ),
+                            eframe::egui::Rect::from_x_y_ranges(
+                                selected.right()..=image_rect.right(),
+                                selected.y_range(),
+                            ),
+                        ] {
+                            painter.rect_filled(outside, 0.0, dim);
+                        }
+                        painter.rect_stroke(
+                            selected,
+                            0.0,
+                            eframe::egui::Stroke::new(
+                                REGION_SELECTION_STROKE_WIDTH,
+                                eframe::egui::Color32::YELLOW,
+                            ),
+                            eframe::egui::StrokeKind::Outside,
+                        );
+                    }
+                    None => {
+                        painter.rect_filled(image_rect, 0.0, dim);
+                    }
+                }
+
+                painter.text(
+                    screen_rect.center_top() + eframe::egui::vec2(0.0, REGION_OVERLAY_HINT_MARGIN),
+                    eframe::egui::Align2::CENTER_TOP,
+                    "Drag to select the region to keep, Escape cancels the capture",
+                    eframe::egui::FontId::proportional(REGION_OVERLAY_FONT_SIZE),
+                    eframe::egui::Color32::WHITE,
+                );
+
+                // pixel readout and magnifier next to the cursor
+                let Some(pointer) = ui.ctx().pointer_latest_pos() else {
+                    return;
+                };
+                let pixel = to_image(pointer)
+                    .floor()
+                    .min((image_size - eframe::egui::Vec2::splat(1.0)).to_pos2());
+                let color = overlay.image.get_pixel(pixel.x as u32, pixel.y as u32);
+                let mut readout = format!(
+                    "{}, {}  #{:02X}{:02X}{:02X}",
+                    pixel.x, pixel.y, color[0], color[1], color[2]
+                );
+                if let Some(s) = selection {
+                    readout.push_str(&format!("  {}×{}", s.width(), s.height()));
+                }
+
+                let magnifier_rect = {
+                    let size = eframe::egui::Vec2::splat(MAGNIFIER_SIZE);
+                    let mut min = pointer + eframe::egui::Vec2::splat(MAGNIFIER_CURSOR_OFFSET);
+                    // flip to the other side of the cursor near the screen edges
+                    if min.x + size.x > screen_rect.right() {
+                        min.x = pointer.x - MAGNIFIER_CURSOR_OFFSET - size.x;
+                    }
+                    if min.y + size.y + REGION_OVERLAY_FONT_SIZE * 2.0 > screen_rect.bottom() {
+                        min.y = pointer.y
+                            - MAGNIFIER_CURSOR_OFFSET
+                            - size.y
+                            - REGION_OVERLAY_FONT_SIZE * 2.0;
+                    }
+                    eframe::egui::Rect::from_min_size(min, size)
+                };
+                let magnified_pixels = MAGNIFIER_SIZE / MAGNIFIER_ZOOM;
+                let magnified_center = pixel + eframe::egui::Vec2::splat(0.5);
+                let magnifier_uv = eframe::egui::Rect::from_center_size(
+                    (magnified_center.to_vec2() / image_size).to_pos2(),
+                    eframe::egui::Vec2::splat(magnified_pixels) / image_size,
+                );
+                painter.rect_filled(magnifier_rect, 0.0, eframe::egui::Color32::BLACK);
+                painter.image(
+                    overlay.texture.id(),
+                    magnifier_rect,
+                    magnifier_uv,
+                    eframe::egui::Color32::WHITE,
+                );
+                painter.rect_stroke(
+                    eframe::egui::Rect::from_center_size(
+                        magnifier_rect.center(),
+                        eframe::egui::Vec2::splat(MAGNIFIER_ZOOM),
+                    ),
+                    0.0,
+                    eframe::egui::Stroke::new(1.0, eframe::egui::Color32::RED),
+                    eframe::egui::StrokeKind::Outside,
+                );
+                painter.rect_stroke(
+                    magnifier_rect,
+                    0.0,
+                    eframe::egui::Stroke::new(1.0, eframe::egui::Color32::WHITE),
+                    eframe::egui::StrokeKind::Outside,
+                );
+                painter.text(
+                    magnifier_rect.center_bottom()
+                        + eframe::egui::vec2(0.0, REGION_OVERLAY_FONT_SIZE / 2.0),
+                    eframe::egui::Align2::CENTER_TOP,
+                    readout,
+                    eframe::egui::FontId::monospace(REGION_OVERLAY_FONT_SIZE),
+                    eframe::egui::Color32::WHITE,
+                );
+            });
+
+        if let Some(result) = overlay.result {
+            // the capture task only holds the lock briefly, retry on the next frame otherwise
+            match self.rayshot_state.region_selection.try_lock() {
+                Ok(mut region_selection) => {
+                    if let Some(request) = region_selection.take() {
+                        let _ = request.reply.send(result);
+                    }
+                    self.region_overlay = None;
+                    ctx.send_viewport_cmd(eframe::egui::ViewportCommand::Fullscreen(false));
+                }
+                Err(_) => ctx.request_repaint(),
+            }
+        }
+        true
+    }
+}
+
+impl RayshotApp {
+    // a plain click shows the entry, ctrl toggles it in the selection and shift selects the
+    // range from the last clicked entry
+    fn click_thumbnail(
+        &mut self,
+        ctx: &eframe::egui::Context,
+        entries: &[crate::ScreenshotEntry],
+        visible: &[bool],
+        index: usize,
+        modifiers: eframe::egui::Modifiers,
+    ) {
+        if modifiers.shift {
+            let anchor = self
+                .selection_anchor
+                .unwrap_or(index)
+                .min(entries.len() - 1);
+            if !modifiers.command {
+                self.selection.clear();
+            }
+            let range = anchor.min(index)..=anchor.max(index);
+            self.selection.extend(
+                entries[range.clone()]
+                    .iter()
+                    .zip(&visible[range])
+                    .filter(|(_, visible)| **visible)
+                    .map(|(entry, _)| entry.filename.to_string()),
+            );
+            return;
+        }
+        self.selection_anchor = Some(index);
+        if modifiers.command {
+            let filename = entries[index].filename.to_string();
+            if !self.selection.remove(&filename) {
+                self.selection.insert(filename);
+            }
+            return;
+        }
+        self.selection.clear();
+        let rayshot_state = self.rayshot_state.clone();
+        let egui_ctx = ctx.clone();
+        tokio::task::spawn(async move {
+            rayshot_state.select_screenshot_index(index).await;
+            rayshot_state.manage_texture_cache(&egui_ctx).await;
+            egui_ctx.request_repaint();
+        });
+    }
+
+    fn select_where(
+        &mut self,
+        entries: &[crate::ScreenshotEntry],
+        predicate: impl Fn(&crate::ScreenshotEntry) -> bool,
+    ) {
+        self.selection = entries
+            .iter()
+            .filter(|entry| predicate(entry))
+            .map(|entry| entry.filename.to_string())
+            .collect();
+    }
+
+    fn selection_bar(&mut self, ui: &mut eframe::egui::Ui, entries: &[crate::ScreenshotEntry]) {
+        ui.horizontal(|ui| {
+            ui.label("☑ Select:");
+            if ui.button("All").clicked() {
+                self.select_where(entries, |_| true);
+            }
+            if ui.button("Local").clicked() {
+                self.select_where(entries, |entry| {
+                    entry
+                        .file_location
+                        .try_lock()
+                        .is_ok_and(|location| matches!(*location, crate::FileLocation::Local))
+                });
+            }
+            if ui.button("Trashed").clicked() {
+                self.select_where(entries, |entry| {
+                    entry
+                        .file_location
+                        .try_lock()
+                        .is_ok_and(|location| matches!(*location, crate::FileLocation::Trash))
+                });
+            }
+            if ui.button("Failed").clicked() {
+                self.select_where(entries, |entry| {
+                    entry.state.try_lock().is_ok_and(|state| state.failed)
+                });
+            }
+            ui.add(
+                eframe::egui::TextEdit::singleline(&mut self.selection_filter)
+                    .hint_text("filename contains")
+                    .desired_width(SELECTION_FILTER_WIDTH),
+            );
+            if ui
+                .add_enabled(
+                    !self.selection_filter.is_empty(),
+                    eframe::egui::Button::new("Matching"),
+                )
+                .clicked()
+            {
+                let needle = self.selection_filter.to_lowercase();
+                self.select_where(entries, |entry| {
+                    entry.filename.to_lowercase().contains(&needle)
+                });
+            }
+            if !self.selection.is_empty() && ui.button("✖ None").clicked() {
+                self.selection.clear();
+            }
+        });
+    }
+
+    // acts on the selected entries among `entries`, those the filter hides stay untouched
+    fn bulk_action_bar(
+        &mut self,
+        ui: &mut eframe::egui::Ui,
+        ctx: &eframe::egui::Context,
+        entries: &[crate::ScreenshotEntry],
+        bulk_running: bool,
+    ) {
+        let selected: Vec<_> = entries
+            .iter()
+            .filter(|entry| self.selection.contains(entry.filename.as_str()))
+            .cloned()
+            .collect();
+        if selected.is_empty() {
+            return;
+        }
+        let mut action = None;
+        ui.horizontal(|ui| {
+            ui.label(format!("{} selected", selected.len()));
+            ui.separator();
+            ui.add_enabled_ui(!bulk_running, |ui| {
+                if ui.button("🗑 Trash").clicked() {
+                    action = Some(crate::storage::BulkAction::Trash);
+                }
+                if ui.button("♻ Restore").clicked() {
+                    action = Some(crate::storage::BulkAction::Restore);
+                }
+                ui.separator();
+                ui.label("Folder:");
+                ui.add(
+                    eframe::egui::TextEdit::singleline(&mut self.bulk_dir)
+                        .desired_width(BULK_DIR_WIDTH),
+                );
+                let has_dir = !self.bulk_dir.trim().is_empty();
+                let bulk_dir = std::path::PathBuf::from(self.bulk_dir.trim());
+                if ui
+                    .add_enabled(has_dir, eframe::egui::Button::new("📁 Copy"))
+                    .clicked()
+                {
+                    action = Some(crate::storage::BulkAction::CopyTo(bulk_dir.clone()));
+                }
+                if ui
+                    .add_enabled(has_dir, eframe::egui::Button::new("📤 Export"))
+                    .clicked()
+                {
+                    action = Some(crate::storage::BulkAction::ExportTo(
+                        bulk_dir,
+                        self.bulk_format,
+                    ));
+                }
+                ui.separator();
+                ui.label("Format:");
+                eframe::egui::ComboBox::from_id_salt("bulk_format")
+                    .selected_text(self.bulk_format.extension())
+                    .show_ui(ui, |ui| {
+                        for format in crate::storage::OutputFormat::ALL {
+                            ui.selectable_value(&mut self.bulk_format, format, format.extension());
+                        }
+                    });
+                if ui.button("🔁 Re-encode").clicked() {
+                    action = Some(crate::storage::BulkAction::Reencode(self.bulk_format));
+                }
+            });
+        });
+        if let Some(action) = action {
+            tokio::task::spawn(crate::storage::run_bulk_action(
+                self.rayshot_state.clone(),
+                ctx.clone(),
+                selected,
+                action,
+            ));
+        }
+    }
+
+    fn filter_bar(&mut self, ui: &mut eframe::egui::Ui, ctx: &eframe::egui::Context) {
+        let previous = self.filter_bar.filter.clone();
+        let bar = &mut self.filter_bar;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("🔍 Filter:");
+            eframe::egui::ComboBox::from_id_salt("filter_location")
+                .selected_text(match bar.filter.location {
+                    None => "Local and trashed",
+                    Some(crate::FileLocation::Local) => "📂 Local",
+                    Some(crate::FileLocation::Trash) => "🗑 Trashed",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut bar.filter.location, None, "Local and trashed");
+                    ui.selectable_value(
+                        &mut bar.filter.location,
+                        Some(crate::FileLocation::Local),
+                        "📂 Local",
+                    );
+                    ui.selectable_value(
+                        &mut bar.filter.location,
+                        Some(crate::FileLocation::Trash),
+                        "🗑 Trashed",
+                    );
+                });
+            ui.checkbox(&mut bar.filter.failed_only, "❌ Failed");
+            ui.separator();
+
+            ui.label("📅");
+            bar.filter.captured_from = date_field(ui, &mut bar.captured_from, "from");
+            ui.label("–");
+            bar.filter.captured_to = date_field(ui, &mut bar.captured_to, "to");
+            ui.separator();
+
+            for (text, hint) in [
+                (&mut bar.filter.app_name, "app name"),
+                (&mut bar.filter.tag, "tag"),
+                (&mut bar.filter.filename, "filename contains"),
+            ] {
+                ui.add(
+                    eframe::egui::TextEdit::singleline(text)
+                        .hint_text(hint)
+                        .desired_width(FILTER_TEXT_WIDTH),
+                );
+            }
+            ui.separator();
+
+            eframe::egui::ComboBox::from_id_salt("filter_rating")
+                .selected_text(match bar.filter.min_rating {
+                    0 => "Any rating".to_string(),
+                    rating => format!("{}+", "★".repeat(rating as usize)),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut bar.filter.min_rating, 0, "Any rating");
+                    for rating in 1..=crate::metadata::MAX_RATING {
+                        ui.selectable_value(
+                            &mut bar.filter.min_rating,
+                            rating,
+                            format!("{}+", "★".repeat(rating as usize)),
+                        );
+                    }
+                });
+            ui.separator();
+
+            ui.label("📐 at least");
+            bar.filter.min_width = size_field(ui, &mut bar.min_width, "width");
+            ui.label("×");
+            bar.filter.min_height = size_field(ui, &mut bar.min_height, "height");
+
+            if bar.filter.is_active() && ui.button("✖ Clear").clicked() {
+                *bar = FilterBar::default();
+            }
+        });
+
+        if self.filter_bar.filter != previous {
+            let rayshot_state = self.rayshot_state.clone();
+            let egui_ctx = ctx.clone();
+            let filter = self.filter_bar.filter.clone();
+            tokio::task::spawn(async move {
+                rayshot_state.set_gallery_filter(filter).await;
+                rayshot_state.manage_texture_cache(&egui_ctx).await;
+                egui_ctx.request_repaint();
+            });
+        }
+    }
+
+    fn annotation_toolbar(
+        &mut self,
+        ui: &mut eframe::egui::Ui,
+        ctx: &eframe::egui::Context,
+        entry: &crate::ScreenshotEntry,
+        annotations: &[crate::annotation::Annotation],
+    ) {
+        let Some(editor) = self.annotation_editor.as_mut() else {
+            return;
+        };
+        let (mut remove_last, mut clear, mut export) = (false, false, false);
+        ui.horizontal_wrapped(|ui| {
+            for tool in crate::annotation::Tool::ALL {
+                if ui
+                    .selectable_label(editor.tool == tool, tool.label())
+                    .clicked()
+                    && editor.tool != tool
+                {
+                    editor.tool = tool;
+                    editor.size = tool.default_size();
+                    editor.drag.clear();
+                }
+            }
+            ui.separator();
+
+            let tool = editor.tool;
+            if !matches!(
+                tool,
+                crate::annotation::Tool::Pixelate | crate::annotation::Tool::Blur
+            ) {
+                ui.color_edit_button_srgba(&mut editor.color);
+            }
+            if tool != crate::annotation::Tool::Highlight {
+                ui.add(
+                    eframe::egui::Slider::new(&mut editor.size, 1.0..=ANNOTATION_MAX_SIZE).text(
+                        match tool {
+                            crate::annotation::Tool::Text | crate::annotation::Tool::Callout => {
+                                "size"
+                            }
+                            crate::annotation::Tool::Pixelate => "block",
+                            crate::annotation::Tool::Blur => "strength",
+                            _ => "width",
+                        },
+                    ),
+                );
+            }
+            if tool == crate::annotation::Tool::Text {
+                ui.add(
+                    eframe::egui::TextEdit::singleline(&mut editor.text)
+                        .hint_text("text, then click to place")
+                        .desired_width(ANNOTATION_TEXT_WIDTH),
+                );
+            }
+            ui.separator();
+
+            let has_annotations = !annotations.is_empty();
+            remove_last = ui
+                .add_enabled(has_annotations, eframe::egui::Button::new("↩ Remove last"))
+                .clicked();
+            clear = ui
+                .add_enabled(has_annotations, eframe::egui::Button::new("🗑 Clear"))
+                .clicked();
+            export = ui
+                .add_enabled(
+                    has_annotations,
+                    eframe::egui::Button::new("📤 Export annotated copy"),
+                )
+                .on_hover_text(format!(
+                    "Saves a copy with the annotations burnt in to '{}'",
+                    self.rayshot_state.config.export_dir.display()
+                ))
+                .clicked();
+            if let Ok(exported) = editor.exported.try_lock() {
+                if let Some(path) = &*exported {
+                    ui.label(format!("Exported to {}", path.display()));
+                }
+            }
+        });
+        let exported = editor.exported.clone();
+
+        if remove_last {
+            self.update_metadata(ctx, entry.filename.to_string(), |metadata| {
+                metadata.annotations.pop();
+            });
+        }
+        if clear {
+            self.update_metadata(ctx, entry.filename.to_string(), |metadata| {
+                metadata.annotations.clear()
+            });
+        }
+        if export {
+            let rayshot_state = self.rayshot_state.clone();
+            let egui_ctx = ctx.clone();
+            let entry = entry.clone();
+            let annotations = annotations.to_vec();
+            tokio::task::spawn(async move {
+                let config = rayshot_state.config.clone();
+                let trash = rayshot_state.freedesktop_trash.clone();
+                let filename = entry.filename.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    let _file_lock = entry.file_lock.blocking_lock();
+                    let source = entry.file_location.blocking_lock().file_path(
+                        &config,
+                        trash.as_ref(),
+                        &filename,
+                    )?;
+                    crate::storage::export_annotated(
+                        &source,
+                        &filename,
+                        &config.export_dir,
+                        &annotations,
+                    )
+                })
+                .await
+                .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+                match result {
+                    Ok(path) => *exported.lock().await = Some(path),
+                    Err(e) => {
+                        crate::state::report_error(
+                            &rayshot_state,
+                            &egui_ctx,
+                            format!("Failed to export the annotated screenshot: {}", e),
+                        )
+                        .await
+                    }
+                }
+                egui_ctx.request_repaint();
+            });
+        }
+    }
+
+    fn edit_toolbar(
+        &mut self,
+        ui: &mut eframe::egui::Ui,
+        ctx: &eframe::egui::Context,
+        entry: &crate::ScreenshotEntry,
+    ) {
+        let Some(editor) = self.image_editor.as_mut() else {
+            return;
+        };
+        // queued edits are for the entry they were queued on
+        if editor.filename != entry.filename.as_str() {
+            editor.filename = entry.filename.to_string();
+            editor.edits.clear();
+        }
+        let dimensions = entry
+            .demension
+            .try_lock()
+            .ok()
+            .and_then(|dimensions| *dimensions);
+        let mut apply = false;
+        ui.horizontal_wrapped(|ui| {
+            for (label, edit) in [
+                (
+                    "⟲ 90°",
+                    crate::edit::Edit::Rotate(crate::edit::Rotation::ThreeQuarters),
+                ),
+                (
+                    "⟳ 90°",
+                    crate::edit::Edit::Rotate(crate::edit::Rotation::Quarter),
+                ),
+                (
+                    "🔄 180°",
+                    crate::edit::Edit::Rotate(crate::edit::Rotation::Half),
+                ),
+                ("⬌ Flip", crate::edit::Edit::FlipHorizontal),
+                ("⬍ Flip", crate::edit::Edit::FlipVertical),
+            ] {
+                if ui.button(label).on_hover_text(edit.label()).clicked() {
+                    editor.edits.push(edit);
+                }
+            }
+            ui.separator();
+
+            let [x, y, width, height] = &mut editor.crop;
+            let crop = (
+                size_field(ui, x, "x"),
+                size_field(ui, y, "y"),
+                size_field(ui, width, "width"),
+                size_field(ui, height, "height"),
+            );
+            if let (Some(x), Some(y), Some(width), Some(height)) = crop {
+                if ui.button("✂ Crop").clicked() {
+                    editor.edits.push(crate::edit::Edit::Crop {
+                        x,
+                        y,
+                        width,
+                        height,
+                    });
+                }
+            }
+            ui.menu_button("📐 Aspect", |ui| {
+                for (width, height) in crate::edit::ASPECT_PRESETS {
+                    if ui.button(format!("{}:{}", width, height)).clicked() {
+                        editor
+                            .edits
+                            .push(crate::edit::Edit::AspectRatio { width, height });
+                        ui.close_menu();
+                    }
+                }
+            });
+            ui.separator();
+
+            let [width, height] = &mut editor.resize;
+            let resize = (
+                size_field(ui, width, "width"),
+                size_field(ui, height, "height"),
+            );
+            eframe::egui::ComboBox::from_id_salt("resize_filter")
+                .selected_text(editor.filter.name())
+                .show_ui(ui, |ui| {
+                    for filter in crate::edit::ResizeFilter::ALL {
+                        ui.selectable_value(&mut editor.filter, filter, filter.name());
+                    }
+                });
+            if let (Some(width), Some(height)) = resize {
+                if ui.button("↔ Resize").clicked() {
+                    editor.edits.push(crate::edit::Edit::Resize {
+                        width,
+                        height,
+                        filter: editor.filter,
+                    });
+                }
+            }
+        });
+
+        if editor.edits.is_empty() {
+            return;
+        }
+        ui.horizontal_wrapped(|ui| {
+            let labels: Vec<_> = editor.edits.iter().map(crate::edit::Edit::label).collect();
+            ui.label(labels.join(" → "));
+            // what the queue makes of the current size, a crop may no longer fit
+            let output_size = dimensions.map(|(width, height)| {
+                editor
+                    .edits
+                    .iter()
+                    .try_fold((width as u32, height as u32), |(width, height), edit| {
+                        edit.output_size(width, height)
+                    })
+            });
+            match &output_size {
+                Some(Ok((width, height))) => {
+                    ui.label(format!("= {}×{} px", width, height));
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(eframe::egui::Color32::RED, e);
+                }
+                None => {}
+            }
+            if ui.button("↩ Remove last").clicked() {
+                editor.edits.pop();
+            }
+            if ui.button("✖ Discard").clicked() {
+                editor.edits.clear();
+            }
+            apply = ui
+                .add_enabled(
+                    !matches!(output_size, Some(Err(_))),
+                    eframe::egui::Button::new("✔ Apply"),
+                )
+                .on_hover_text(format!(
+                    "Rewrites the file, {} reverts it",
+                    self.rayshot_state.config.hotkeys.undo
+                ))
+                .clicked();
+        });
+
+        if apply {
+            let edits = std::mem::take(&mut editor.edits);
+            let rayshot_state = self.rayshot_state.clone();
+            let egui_ctx = ctx.clone();
+            let entry = entry.clone();
+            tokio::task::spawn(async move {
+                if let Some(operation) =
+                    crate::storage::edit_entry(&rayshot_state, &egui_ctx, &entry, edits).await
+                {
+                    crate::journal::record(&rayshot_state, &egui_ctx, vec![operation]).await;
+                }
+            });
+        }
+    }
+
+    // the main image in annotation mode, dragging or clicking on it adds to the layer
+    fn annotation_view(
+        &mut self,
+        ui: &mut eframe::egui::Ui,
+        ctx: &eframe::egui::Context,
+        entry: &crate::ScreenshotEntry,
+        texture: &eframe::egui::TextureHandle,
+        display_size: eframe::egui::Vec2,
+        annotations: &[crate::annotation::Annotation],
+    ) {
+        let Some(editor) = self.annotation_editor.as_mut() else {
+            return;
+        };
+
+        // pixelate and blur are shown from a copy with them applied once it is rendered
+        let mut texture_id = texture.id();
+        let effects: Vec<_> = annotations
+            .iter()
+            .filter(|annotation| annotation.is_effect())
+            .cloned()
+            .collect();
+        if !effects.is_empty() {
+            let wanted = (entry.filename.to_string(), effects);
+            if let Ok(preview) = editor.effects_preview.try_lock() {
+                if let Some(preview) = preview
+                    .as_ref()
+                    .filter(|preview| preview.filename == wanted.0 && preview.effects == wanted.1)
+                {
+                    texture_id = preview.texture.id();
+                }
+            }
+            if editor.preview_requested.as_ref() != Some(&wanted) {
+                editor.preview_requested = Some(wanted.clone());
+                editor.preview_generation += 1;
+                let generation = editor.preview_generation;
+                let effects_preview = editor.effects_preview.clone();
+                let rayshot_state = self.rayshot_state.clone();
+                let egui_ctx = ctx.clone();
+                let entry = entry.clone();
+                tokio::task::spawn_blocking(move || {
+                    let (filename, effects) = wanted;
+                    let _file_lock = entry.file_lock.blocking_lock();
+                    let file_path = entry.file_location.blocking_lock().file_path(
+                        &rayshot_state.config,
+                        rayshot_state.freedesktop_trash.as_ref(),
+                        &filename,
+                    );
+                    match file_path
+                        .map_err(image::ImageError::IoError)
+                        .and_then(image::open)
+                    {
+                        Ok(image) => {
+                            let mut image_buffer = image.to_rgba8();
+                            crate::annotation::apply_effects(&mut image_buffer, &effects);
+                            let texture = egui_ctx.load_texture(
+                                format!("{} annotated", filename),
+                                eframe::epaint::ColorImage::from_rgba_unmultiplied(
+                                    [
+                                        image_buffer.width() as usize,
+                                        image_buffer.height() as usize,
+                                    ],
+                                    image_buffer.as_raw(),
+                                ),
+                                Default::default(),
+                            );
+                            let mut effects_preview = effects_preview.blocking_lock();
+                            if effects_preview
+                                .as_ref()
+                                .is_none_or(|preview| preview.generation < generation)
+                            {
+                                *effects_preview = Some(EffectsPreview {
+                                    generation,
+                                    filename,
+                                    effects,
+                                    texture,
+                                });
+                            }
+                        }
+                        Err(e) => {
+                            let err_str = format!(
+                                "Failed to render the annotations of '{}': {}",
+                                filename, e
+                            );
+                            eprintln!("{}", err_str);
+                            rayshot_state.error_messages.blocking_lock().push(err_str);
+                        }
+                    }
+                    egui_ctx.request_repaint();
+                });
+            }
+        }
+
+        let response = ui.add(
+            eframe::egui::Image::new((texture_id, display_size))
+                .sense(eframe::egui::Sense::click_and_drag()),
+        );
+        let rect = response.rect;
+        let image_size = texture.size_vec2();
+        let scale = display_size.x / image_size.x;
+        let to_screen = |[x, y]: [f32; 2]| rect.min + eframe::egui::vec2(x, y) * scale;
+        let to_image = |pos: eframe::egui::Pos2| {
+            let offset = (pos - rect.min) / scale;
+            [
+                offset.x.clamp(0.0, image_size.x),
+                offset.y.clamp(0.0, image_size.y),
+            ]
+        };
+
+        let mut finished = None;
+        let pointer = response.interact_pointer_pos();
+        if editor.tool.is_click() {
+            if response.clicked() {
+                finished = pointer.map(|pos| vec![to_image(pos)]);
+            }
+        } else {
+            if response.drag_started() {
+                let origin = ctx.input(|i| i.pointer.press_origin()).or(pointer);
+                editor.drag = origin.map(|pos| vec![to_image(pos)]).unwrap_or_default();
+            }
+            if response.dragged() && !editor.drag.is_empty() {
+                if let Some(pos) = pointer {
+                    let point = to_image(pos);
+                    if editor.tool != crate::annotation::Tool::Freehand {
+                        editor.drag.truncate(1);
+                    }
+                    if editor.drag.last() != Some(&point) {
+                        editor.drag.push(point);
+                    }
+                }
+            }
+            if response.drag_stopped() {
+                finished = Some(std::mem::take(&mut editor.drag));
+            }
+        }
+
+        let next_callout = annotations
+            .iter()
+            .filter_map(|annotation| match annotation.shape {
+                crate::annotation::Shape::Callout { number, .. } => Some(number),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let [r, g, b, a] = editor.color.to_srgba_unmultiplied();
+        let annotation = |points: &[[f32; 2]]| {
+            editor
+                .tool
+                .shape(points, &editor.text, next_callout)
+                .map(|shape| crate::annotation::Annotation {
+                    shape,
+                    color: [r, g, b, a],
+                    size: editor.size,
+                })
+        };
+        let pending = annotation(&editor.drag);
+        let added = finished.and_then(|points| annotation(&points));
+
+        let painter = ui.painter_at(rect);
+        for annotation in annotations.iter().chain(&pending).chain(&added) {
+            paint_annotation(&painter, annotation, to_screen, scale);
+        }
+
+        if let Some(added) = added {
+            if editor.tool == crate::annotation::Tool::Text {
+                editor.text.clear();
+            }
+            self.update_metadata(ctx, entry.filename.to_string(), move |metadata| {
+                metadata.annotations.push(added)
+            });
+        }
+    }
+
+    // the rename row of the current entry, the rename is undone like a move
+    fn rename_bar(
+        &mut self,
+        ui: &mut eframe::egui::Ui,
+        ctx: &eframe::egui::Context,
+        entry: &crate::ScreenshotEntry,
+    ) {
+        let Some(input) = self
+            .rename_input
+            .as_mut()
+            .filter(|input| input.filename == *entry.filename)
+        else {
+            return;
+        };
+        let renamed =
+            crate::storage::renamed_filename(&input.filename, &input.stem).and_then(|renamed| {
+                match renamed == input.filename {
+                    true => Err("the name is unchanged".to_string()),
+                    false => Ok(renamed),
+                }
+            });
+        let mut submitted = false;
+        ui.horizontal(|ui| {
+            ui.label("✏ New name:");
+            let field = ui.add(
+                eframe::egui::TextEdit::singleline(&mut input.stem)
+                    .desired_width(RENAME_INPUT_WIDTH),
+            );
+            submitted = field.lost_focus() && ui.input(|i| i.key_pressed(eframe::egui::Key::Enter));
+            let button = ui.add_enabled(renamed.is_ok(), eframe::egui::Button::new("Rename"));
+            submitted = (submitted || button.clicked()) && renamed.is_ok();
+            match &renamed {
+                Ok(renamed) => ui.label(format!("→ {}", renamed)),
+                Err(e) => ui.colored_label(eframe::egui::Color32::GRAY, e),
+            };
+        });
+        let (true, Ok(renamed)) = (submitted, renamed) else {
+            return;
+        };
+        self.rename_input = None;
+        let rayshot_state = self.rayshot_state.clone();
+        let egui_ctx = ctx.clone();
+        let entry = entry.clone();
+        tokio::task::spawn(async move {
+            if let Some(operation) =
+                crate::storage::rename_entry(&rayshot_state, &egui_ctx, &entry, renamed).await
+            {
+                crate::journal::record(&rayshot_state, &egui_ctx, vec![operation]).await;
+            }
+        });
+    }
+
+    fn update_metadata(
+        &self,
+        ctx: &eframe::egui::Context,
+        filename: String,
+        update: impl FnOnce(&mut crate::metadata::ScreenshotMetadata) + Send + 'static,
+    ) {
+        let rayshot_state = self.rayshot_state.clone();
+        let egui_ctx = ctx.clone();
+        tokio::task::spawn(async move {
+            crate::metadata::update(&rayshot_state, &egui_ctx, |metadata| {
+                update(metadata.entry(&filename))
+            })
+            .await;
+            egui_ctx.request_repaint();
+        });
+    }
+
+    fn save_notes_draft(&mut self, ctx: &eframe::egui::Context) {
+        let Some(draft) = self.notes_draft.as_mut().filter(|draft| draft.dirty) else {
+            return;
+        };
+        draft.dirty = false;
+        let (filename, notes) = (draft.filename.clone(), draft.text.clone());
+        self.update_metadata(ctx, filename, move |metadata| metadata.notes = notes);
+    }
+
+    fn metadata_editor(
+        &mut self,
+        ui: &mut eframe::egui::Ui,
+        ctx: &eframe::egui::Context,
+        filename: &str,
+        metadata: Option<&crate::metadata::ScreenshotMetadata>,
+    ) {
+        let Some(metadata) = metadata else {
+            ui.label("🏷 Loading...");
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("⭐ Rating:");
+            for rating in 1..=crate::metadata::MAX_RATING {
+                let star = if rating <= metadata.rating {
+                    eframe::egui::RichText::new("★").color(eframe::egui::Color32::GOLD)
+                } else {
+                    eframe::egui::RichText::new("☆")
+                };
+                if ui
+                    .add(eframe::egui::Label::new(star).sense(eframe::egui::Sense::click()))
+                    .on_hover_text("Click the current rating again to clear it")
+                    .clicked()
+                {
+                    let rating = if rating == metadata.rating { 0 } else { rating };
+                    self.update_metadata(ctx, filename.to_string(), move |metadata| {
+                        metadata.rating = rating
+                    });
+                }
+            }
+        });
+
+        ui.add_space(5.0);
+
+        ui.label("🏷 Tags:");
+        ui.horizontal_wrapped(|ui| {
+            for tag in &metadata.tags {
+                if ui
+                    .button(format!("{} ✖", tag))
+                    .on_hover_text("Remove this tag")
+                    .clicked()
+                {
+                    let tag = tag.clone();
+                    self.update_metadata(ctx, filename.to_string(), move |metadata| {
+                        metadata.tags.retain(|other| *other != tag)
+                    });
+                }
+            }
+            let input = ui.add(
+                eframe::egui::TextEdit::singleline(&mut self.tag_input)
+                    .hint_text("new tag")
+                    .desired_width(TAG_INPUT_WIDTH),
+            );
+            let submitted =
+                input.lost_focus() && ui.input(|i| i.key_pressed(eframe::egui::Key::Enter));
+            let tag = self.tag_input.trim().to_string();
+            let valid = crate::metadata::validate_tag(&tag).is_ok();
+            if (ui
+                .add_enabled(valid, eframe::egui::Button::new("➕"))
+                .clicked()
+                || submitted)
+                && valid
+            {
+                self.tag_input.clear();
+                if !metadata.tags.contains(&tag) {
+                    self.update_metadata(ctx, filename.to_string(), move |metadata| {
+                        metadata.tags.push(tag)
+                    });
+                }
+            }
+        });
+
+        ui.add_space(5.0);
+
+        // a draft for another entry is saved before this one's notes are shown
+        if self
+            .notes_draft
+            .as_ref()
+            .is_none_or(|draft| draft.filename != filename)
+        {
+            self.save_notes_draft(ctx);
+            self.notes_draft = Some(NotesDraft {
+                filename: filename.to_string(),
+                text: metadata.notes.clone(),
+                dirty: false,
+            });
+        }
+        let Some(draft) = self.notes_draft.as_mut() else {
+            return;
+        };
+        // follow changes made elsewhere while nothing is typed
+        if !draft.dirty && draft.text != metadata.notes {
+            draft.text = metadata.notes.clone();
+        }
+        ui.label("📝 Notes:");
+        let notes = ui.add(
+            eframe::egui::TextEdit::multiline(&mut draft.text)
+                .desired_rows(NOTES_ROWS)
+                .desired_width(NOTES_WIDTH),
+        );
+        if notes.changed() {
+            draft.dirty = true;
+        }
+        if notes.lost_focus() {
+            self.save_notes_draft(ctx);
+        }
+    }
+}
+
+// the UI counterpart of `annotation::flatten`, pixelate and blur regions are only outlined
+fn paint_annotation(
+    painter: &eframe::egui::Painter,
+    annotation: &crate::annotation::Annotation,
+    to_screen: impl Fn([f32; 2]) -> eframe::egui::Pos2,
+    scale: f32,
+) {
+    let color32 = |[r, g, b, a]: [u8; 4]| eframe::egui::Color32::from_rgba_unmultiplied(r, g, b, a);
+    let color = color32(annotation.color);
+    match &annotation.shape {
+        crate::annotation::Shape::Highlight { from, to } => {
+            painter.rect_filled(
+                eframe::egui::Rect::from_two_pos(to_screen(*from), to_screen(*to)),
+                0.0,
+                color32(annotation.highlight_color()),
+            );
+        }
+        crate::annotation::Shape::Pixelate { from, to }
+        | crate::annotation::Shape::Blur { from, to } => {
+            painter.rect_stroke(
+                eframe::egui::Rect::from_two_pos(to_screen(*from), to_screen(*to)),
+                0.0,
+                eframe::egui::Stroke::new(1.0, eframe::egui::Color32::GRAY),
+                eframe::egui::StrokeKind::Outside,
+            );
+        }
+        crate::annotation::Shape::Text { at, text } => {
+            painter.text(
+                to_screen(*at),
+                eframe::egui::Align2::LEFT_TOP,
+                text,
+                eframe::egui::FontId::proportional(annotation.size * scale),
+                color,
+            );
+        }
+        crate::annotation::Shape::Callout { at, number } => {
+            let center = to_screen(*at);
+            painter.circle_filled(center, annotation.size / 2.0 * scale, color);
+            painter.text(
+                center,
+                eframe::egui::Align2::CENTER_CENTER,
+                number.to_string(),
+                eframe::egui::FontId::proportional(
+                    crate::annotation::callout_font_size(annotation.size) * scale,
+                ),
+                color32(crate::annotation::contrasting(annotation.color)),
+            );
+        }
+        _ => {
+            for stroke in annotation.strokes() {
+                painter.add(eframe::egui::Shape::line(
+                    stroke.into_iter().map(&to_screen).collect(),
+                    eframe::egui::Stroke::new(annotation.size * scale, color),
+                ));
+            }
+        }
+    }
+}
+
+// a `YYYY-MM-DD` field, red while its text is not a date
+fn date_field(
+    ui: &mut eframe::egui::Ui,
+    text: &mut String,
+    hint: &str,
+) -> Option<chrono::NaiveDate> {
+    let parse = |text: &str| chrono::NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok();
+    let invalid = parse(text).is_none() && !text.trim().is_empty();
+    let mut field = eframe::egui::TextEdit::singleline(text)
+        .hint_text(format!("{} YYYY-MM-DD", hint))
+        .desired_width(FILTER_DATE_WIDTH);
+    if invalid {
+        field = field.text_color(eframe::egui::Color32::RED);
+    }
+    ui.add(field);
+    parse(text)
+}
+
+// a pixel count field, red while its text is not a number
+fn size_field(ui: &mut eframe::egui::Ui, text: &mut String, hint: &str) -> Option<u32> {
+    let parse = |text: &str| text.trim().parse().ok();
+    let invalid = parse(text).is_none() && !text.trim().is_empty();
+    let mut field = eframe::egui::TextEdit::singleline(text)
+        .hint_text(hint)
+        .desired_width(FILTER_SIZE_WIDTH);
+    if invalid {
+        field = field.text_color(eframe::egui::Color32::RED);
+    }
+    ui.add(field);
+    parse(text)
+}
+
+// gold stars for the thumbnail labels, nothing for unrated entries
+fn rating_label(rating: u8) -> Option<eframe::egui::RichText> {
+    (rating > 0).then(|| {
+        eframe::egui::RichText::new("★".repeat(rating as usize))
+            .color(eframe::egui::Color32::GOLD)
+            .small()
+    })
+}
+
+fn trashed_count(entries: &[crate::ScreenshotEntry]) -> usize {
+    entries
+        .iter()
+        .filter(|entry| {
+            entry
+                .file_location
+                .try_lock()
+                .is_ok_and(|location| matches!(*location, crate::FileLocation::Trash))
+        })
+        .count()
+}
+
+// 1-based position of the entry within its burst and the burst's frame count
+fn burst_position(entries: &[crate::ScreenshotEntry], index: usize) -> Option<(usize, usize)> {
+    let burst_id = entries.get(index)?.burst_id?;
+    let frame = entries[..=index]
+        .iter()
+        .filter(|entry| entry.burst_id == Some(burst_id))
+        .count();
+    let frames = entries
+        .iter()
+        .filter(|entry| entry.burst_id == Some(burst_id))
+        .count();
+    Some((frame, frames))
+}
+
+// the ui code below is vibe coded
+impl eframe::App for RayshotApp {
+    fn update(&mut self, ctx: &eframe::egui::Context, _frame: &mut eframe::Frame) {
+        if self.region_overlay(ctx) {
+            return;
+        }
+        // a focused text field keeps its own undo and keys
+        if !ctx.wants_keyboard_input() {
+            for (shortcut, hotkey) in &self.window_shortcuts {
+                if ctx.input_mut(|i| i.consume_shortcut(shortcut)) {
+                    hotkey
+                        .clone()
+                        .spawn_action(self.rayshot_state.clone(), ctx.clone());
+                }
+            }
+        }
+
+        // Copy data needed for UI without holding locks
+        let entries: Vec<_> = {
+            if let Ok(entries_guard) = self.rayshot_state.screenshot_entries.try_lock() {
+                entries_guard.clone()
+            } else {
+                Vec::new() // Return empty if we can't get lock
+            }
+        };
+        let current_idx = {
+            if let Ok(idx_guard) = self.rayshot_state.cur_screenshot_idx.try_lock() {
+                *idx_guard
+            } else {
+                0
+            }
+        };
+        // the ratings and filter matches for the thumbnails and the current entry's record for
+        // the editor, entries whose location or state is locked stay visible
+        let (ratings, visible, current_metadata) = match self.rayshot_state.metadata.try_lock() {
+            Ok(metadata) => (
+                entries
+                    .iter()
+                    .map(|entry| {
+                        metadata
+                            .get(&entry.filename)
+                            .map_or(0, |metadata| metadata.rating)
+                    })
+                    .collect(),
+                entries
+                    .iter()
+                    .map(|entry| {
+                        let (Ok(location), Ok(state)) =
+                            (entry.file_location.try_lock(), entry.state.try_lock())
+                        else {
+                            return true;
+                        };
+                        self.filter_bar.filter.matches(
+                            &entry.filename,
+                            *location,
+                            state.failed,
+                            metadata.get(&entry.filename),
+                        )
+                    })
+                    .collect(),
+                entries
+                    .get(current_idx)
+                    .map(|entry| metadata.get(&entry.filename).cloned().unwrap_or_default()),
+            ),
+            Err(_) => (vec![0; entries.len()], vec![true; entries.len()], None),
+        };
+        let shown_entries: Vec<_> = entries
+            .iter()
+            .zip(&visible)
+            .filter(|(_, visible)| **visible)
+            .map(|(entry, _)| entry.clone())
+            .collect();
+        let bulk_progress = match self.rayshot_state.bulk_progress.try_lock() {
+            Ok(bulk_progress) => bulk_progress.clone(),
+            Err(_) => None,
+        };
+        let errors: Vec<String> = {
+            if let Ok(errors_guard) = self.rayshot_state.error_messages.try_lock() {
+                errors_guard.clone()
+            } else {
+                Vec::new() // Return empty if we can't get lock
+            }
+        };
+
+        // Top panel with controls
+        eframe::egui::TopBottomPanel::top("top_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("🎯 rayshot");
+                ui.separator();
+
+                if ui.button("🔄 Refresh").clicked() {
+                    ctx.request_repaint();
+                }
+
+                let trashed_count = trashed_count(&entries);
+                if ui
+                    .add_enabled(
+                        trashed_count > 0,
+                        eframe::egui::Button::new("🔥 Empty Trash"),
+                    )
+                    .clicked()
+                {
+                    self.confirm_empty_trash = true;
+                }
+
+                if !errors.is_empty() && ui.button("🗑 Clear Errors").clicked() {
+                    if let Ok(mut errors_guard) = self.rayshot_state.error_messages.try_lock() {
+                        errors_guard.clear();
+                    }
+                }
+
+                ui.separator();
+                ui.label(format!(
+                    "📸 Hotkey: {}",
+                    self.rayshot_state.config.hotkeys.capture_screenshot
+                ));
+
+                ui.separator();
+                ui.label("🎮 Target:");
+                self.profile_switcher(ui, "top_bar_profile_switcher");
+
+                if let Some(bulk_progress) = &bulk_progress {
+                    ui.separator();
+                    ui.add(
+                        eframe::egui::ProgressBar::new(
+                            bulk_progress.done as f32 / bulk_progress.total.max(1) as f32,
+                        )
+                        .desired_width(BULK_PROGRESS_WIDTH)
+                        .text(format!(
+                            "{} {}/{}",
+                            bulk_progress.label, bulk_progress.done, bulk_progress.total
+                        )),
+                    );
+                }
+
+                ui.with_layout(
+                    eframe::egui::Layout::right_to_left(eframe::egui::Align::Center),
+                    |ui| {
+                        if !entries.is_empty() {
+                            ui.label(format!("Current: {}/{}", current_idx + 1, entries.len()));
+                            ui.separator();
+                        }
+                        ui.label(format!("Screenshots: {}", entries.len()));
+                    },
+                );
+            });
+        });
+
+        // Select all and clear the selection, unless a text field has the keyboard
+        if !ctx.wants_keyboard_input() {
+            let (select_all, clear_selection) = ctx.input(|i| {
+                (
+                    i.modifiers.command && i.key_pressed(eframe::egui::Key::A),
+                    i.key_pressed(eframe::egui::Key::Escape),
+                )
+            });
+            if select_all {
+                self.select_where(&shown_entries, |_| true);
+            }
+            if clear_selection {
+                self.selection.clear();
+            }
+        }
+        let mut clicked_thumbnail = None;
+
+        // Main content area
+        eframe::egui::CentralPanel::default().show(ctx, |ui| {
+            if entries.is_empty() {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(WELCOME_SECTION_TOP_SPACING);
+                    ui.heading("Welcome to rayshot!");
+                    ui.add_space(WELCOME_SECTION_MIDDLE_SPACING);
+                    ui.label(format!(
+                        "Press {} to capture a screenshot of the current target",
+                        self.rayshot_state.config.hotkeys.capture_screenshot
+                    ));
+                    ui.add_space(WELCOME_SECTION_BOTTOM_SPACING);
+                    ui.horizontal(|ui| {
+                        ui.label("🎮 Current target:");
+                        self.profile_switcher(ui, "welcome_profile_switcher");
+                    });
+                });
+            } else {
+                // Get current screenshot (current_idx is in natural order)
+                let current_entry = if current_idx < entries.len() {
+                    Some(&entries[current_idx])
+                } else {
+                    entries.last()
+                };
+
+                ui.vertical(|ui| {
+                    // Current screenshot center stage
+                    if let Some(entry) = current_entry {
+                        ui.group(|ui| {
+                            ui.vertical_centered(|ui| {
+                                ui.heading(format!(
+                                    "📷 Current Screenshot ({}/{})",
+                                    current_idx + 1,
+                                    entries.len()
+                                ));
+                                ui.add_space(SCREENSHOT_INFO_SPACING);
+
+                                // Annotation tools
+                                let annotations = current_metadata
+                                    .as_ref()
+                                    .map_or(&[][..], |metadata| &metadata.annotations);
+                                if self.annotation_editor.is_some() {
+                                    self.annotation_toolbar(ui, ctx, entry, annotations);
+                                    ui.add_space(SCREENSHOT_INFO_SPACING);
+                                }
+
+                                // Crop, rotate, flip and resize
+                                if self.image_editor.is_some() {
+                                    self.edit_toolbar(ui, ctx, entry);
+                                    ui.add_space(SCREENSHOT_INFO_SPACING);
+                                }
+
+                                // Large screenshot display
+                                if let Ok(img_lock) = entry.texture_handle.try_lock() {
+                                    if let Some(tex) = &*img_lock {
+                                        let available_rect = ui.available_rect_before_wrap();
+                                        let max_width =
+                                            available_rect.width() * MAIN_IMAGE_WIDTH_RATIO;
+                                        let max_height =
+                                            available_rect.height() * MAIN_IMAGE_HEIGHT_RATIO;
+
+                                        let tex_size = tex.size_vec2();
+                                        let scale = (max_width / tex_size.x)
+                                            .min(max_height / tex_size.y)
+                                            .min(1.0);
+                                        let display_size = tex_size * scale;
+
+                                        if self.annotation_editor.is_some() {
+                                            self.annotation_view(
+                                                ui,
+                                                ctx,
+                                                entry,
+                                                tex,
+                                                display_size,
+                                                annotations,
+                                            );
+                                        } else {
+                                            ui.image((tex.id(), display_size));
+                                        }
+                                    } else {
+                                        ui.add_space(LOADING_PLACEHOLDER_SIZE);
+                                        ui.label("🖼 Texture unloaded (memory limit)");
+                                    }
+                                } else {
+                                    ui.add_space(LOADING_PLACEHOLDER_SIZE);
+                                    ui.label("🖼 Loading...");
+                                }
+
+                                ui.add_space(SCREENSHOT_INFO_SPACING);
+
+                                // Current screenshot info
+                                ui.horizontal(|ui| {
+                                    ui.vertical(|ui| {
+                                        ui.label("📁 Filename:");
+                                        ui.label(
+                                            eframe::egui::RichText::new(entry.filename.as_str())
+                                                .monospace(),
+                                        );
+
+                                        ui.add_space(5.0);
+
+                                        // File location status
+                                        if let Ok(location) = entry.file_location.try_lock() {
+                                            match *location {
+                                                crate::FileLocation::Local => {
+                                                    ui.colored_label(
+                                                        eframe::egui::Color32::GREEN,
+                                                        "📂 Local",
+                                                    );
+                                                }
+                                                crate::FileLocation::Trash => {
+                                                    ui.colored_label(
+                                                        eframe::egui::Color32::RED,
+                                                        "🗑 Trashed",
+                                                    );
+                                                }
+                                            }
+                                        } else {
+                                            ui.label("📍 Location: Loading...");
+                                        }
+                                    });
+
+                                    ui.separator();
+
+                                    ui.vertical(|ui| {
+                                        // File size
+                                        ui.label("📏 File Size:");
+                                        if let Ok(file_size) = entry.file_size.try_lock() {
+                                            if let Some(size) = *file_size {
+                                                let size_str = if size >= 1_048_576 {
+                                                    format!("{:.2} MB", size as f64 / 1_048_576.0)
+                                                } else if size >= 1024 {
+                                                    format!("{:.2} KB", size as f64 / 1024.0)
+                                                } else {
+                                                    format!("{} bytes", size)
+                                                };
+                                                ui.label(
+                                                    eframe::egui::RichText::new(size_str)
+                                                        .monospace(),
+                                                );
+                                            } else {
+                                                ui.label("Unknown");
+                                            }
+                                        } else {
+                                            ui.label("Loading...");
+                                        }
+
+                                        ui.add_space(5.0);
+
+                                        // Screenshot dimensions
+                                        ui.label("📐 Dimensions:");
+                                        if let Ok(dimensions) = entry.demension.try_lock() {
+                                            if let Some((width, height)) = *dimensions {
+                                                ui.label(
+                                                    eframe::egui::RichText::new(format!(
+                                                        "{}×{} px",
+                                                        width, height
+                                                    ))
+                                                    .monospace(),
+                                                );
+                                            } else {
+                                                ui.label("Unknown");
+                                            }
+                                        } else {
+                                            ui.label("Loading...");
+                                        }
+                                    });
+
+                                    ui.separator();
+
+                                    ui.vertical(|ui| {
+                                        // Rating, tags and notes
+                                        self.metadata_editor(
+                                            ui,
+                                            ctx,
+                                            entry.filename.as_str(),
+                                            current_metadata.as_ref(),
+                                        );
+                                    });
+
+                                    ui.separator();
+
+                                    ui.vertical(|ui| {
+                                        // Status indicators
+                                        if let Ok(state) = entry.state.try_lock() {
+                                            ui.label("📊 Status:");
+                                            if state.failed {
+                                                ui.colored_label(
+                                                    eframe::egui::Color32::RED,
+                                                    "❌ Failed",
+                                                );
+                                            } else if state.capturing {
+                                                ui.colored_label(
+                                                    eframe::egui::Color32::YELLOW,
+                                                    "📸 Capturing...",
+                                                );
+                                            } else if state.moving {
+                                                ui.colored_label(
+                                                    eframe::egui::Color32::BLUE,
+                                                    "📦 Moving...",
+                                                );
+                                            } else if state.writing {
+                                                ui.colored_label(
+                                                    eframe::egui::Color32::YELLOW,
+                                                    "💾 Writing to disk...",
+                                                );
+                                            } else if state.loading {
+                                                ui.colored_label(
+                                                    eframe::egui::Color32::YELLOW,
+                                                    "⏳ Loading from disk...",
+                                                );
+                                            } else {
+                                                ui.colored_label(
+                                                    eframe::egui::Color32::GREEN,
+                                                    "✅ Saved",
+                                                );
+                                            }
+                                        } else {
+                                            ui.label("📊 Status: Loading...");
+                                        }
+                                    });
+
+                                    ui.separator();
+
+                                    ui.vertical(|ui| {
+                                        // Navigation info
+                                        ui.label("Navigation:");
+                                        ui.label("Left/Right Arrow keys to navigate");
+                                        ui.label("Delete key to trash/restore");
+                                        ui.label(format!(
+                                            "{} to undo, {} to redo",
+                                            self.rayshot_state.config.hotkeys.undo,
+                                            self.rayshot_state.config.hotkeys.redo
+                                        ));
+                                        if entry.burst_id.is_some() {
+                                            ui.label(format!(
+                                                "{} to keep only this frame",
+                                                self.rayshot_state.config.hotkeys.keep_burst_frame
+                                            ));
+                                        }
+                                    });
+                                });
+
+                                ui.add_space(SCREENSHOT_INFO_SPACING);
+
+                                // Action buttons
+                                ui.horizontal(|ui| {
+                                    let annotating = self.annotation_editor.is_some();
+                                    if ui.selectable_label(annotating, "🖊 Annotate").clicked() {
+                                        self.annotation_editor =
+                                            (!annotating).then(AnnotationEditor::new);
+                                    }
+
+                                    let editing = self.image_editor.is_some();
+                                    if ui.selectable_label(editing, "✂ Edit").clicked() {
+                                        self.image_editor = (!editing).then(ImageEditor::default);
+                                    }
+
+                                    let renaming = self
+                                        .rename_input
+                                        .as_ref()
+                                        .is_some_and(|input| input.filename == *entry.filename);
+                                    if ui.selectable_label(renaming, "✏ Rename").clicked() {
+                                        self.rename_input = (!renaming).then(|| RenameInput {
+                                            filename: entry.filename.to_string(),
+                                            stem: std::path::Path::new(entry.filename.as_str())
+                                                .file_stem()
+                                                .unwrap_or_default()
+                                                .to_string_lossy()
+                                                .into_owned(),
+                                        });
+                                    }
+
+                                    if ui.button("📂 Open Folder").clicked() {
+                                        let rayshot_state = self.rayshot_state.clone();
+                                        let egui_ctx = ctx.clone();
+                                        let entry = entry.clone();
+                                        tokio::task::spawn(async move {
+                                            crate::launch::open_folder(
+                                                &rayshot_state,
+                                                &egui_ctx,
+                                                &entry,
+                                            )
+                                            .await;
+                                        });
+                                    }
+
+                                    let external_editor =
+                                        self.rayshot_state.config.external_editor.as_ref();
+                                    let open_with = ui
+                                        .add_enabled(
+                                            external_editor.is_some(),
+                                            eframe::egui::Button::new("🖌 Open with…"),
+                                        )
+                                        .on_hover_text(
+                                            external_editor
+                                                .map(|editor| editor.join(" "))
+                                                .unwrap_or_default(),
+                                        )
+                                        .on_disabled_hover_text(
+                                            "Set external_editor in the config",
+                                        );
+                                    if open_with.clicked() {
+                                        let rayshot_state = self.rayshot_state.clone();
+                                        let egui_ctx = ctx.clone();
+                                        let entry = entry.clone();
+                                        tokio::task::spawn(async move {
+                                            crate::launch::open_in_editor(
+                                                &rayshot_state,
+                                                &egui_ctx,
+                                                &entry,
+                                            )
+                                            .await;
+                                        });
+                                    }
+
+                                    for content in crate::clipboard::ClipboardContent::ALL {
+                                        if ui.button(content.label()).clicked() {
+                                            let rayshot_state = self.rayshot_state.clone();
+                                            let egui_ctx = ctx.clone();
+                                            let entry = entry.clone();
+                                            tokio::task::spawn(async move {
+                                                crate::clipboard::copy_entry(
+                                                    &rayshot_state,
+                                                    &egui_ctx,
+                                                    &entry,
+                                                    content,
+                                                )
+                                                .await;
+                                            });
+                                        }
+                                    }
+
+                                    if let Some((_, frames)) = burst_position(&entries, current_idx)
+                                    {
+                                        let keep_button = ui.button(format!(
+                                            "🏆 Keep this frame, trash the other {}",
+                                            frames - 1
+                                        ));
+                                        if keep_button.clicked() {
+                                            let rayshot_state = self.rayshot_state.clone();
+                                            let egui_ctx = ctx.clone();
+                                            tokio::task::spawn(async move {
+                                                crate::storage::keep_burst_frame(
+                                                    &rayshot_state,
+                                                    &egui_ctx,
+                                                )
+                                                .await;
+                                            });
+                                        }
+                                    }
+                                });
+                                self.rename_bar(ui, ctx, entry);
+                            });
+                        });
+
+                        ui.add_space(SECTION_SEPARATOR_SPACING);
+                    }
+
+                    // All screenshots list below
+                    ui.separator();
+                    self.filter_bar(ui, ctx);
+                    ui.separator();
+                    if self.filter_bar.filter.is_active() {
+                        ui.heading(format!(
+                            "📸 All Screenshots ({} of {} shown)",
+                            shown_entries.len(),
+                            entries.len()
+                        ));
+                    } else {
+                        ui.heading("📸 All Screenshots");
+                    }
+                    ui.add_space(SCREENSHOT_INFO_SPACING);
+                    self.selection_bar(ui, &shown_entries);
+                    self.bulk_action_bar(ui, ctx, &shown_entries, bulk_progress.is_some());
+                    ui.add_space(SCREENSHOT_INFO_SPACING);
+
+                    let scroll_area = eframe::egui::ScrollArea::horizontal()
+                        .auto_shrink([false; 2])
+                        .max_height(HORIZONTAL_LIST_HEIGHT);
+
+                    scroll_area.show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            if shown_entries.is_empty() {
+                                ui.label("No screenshots match the filter");
+                            }
+                            for (index, entry) in entries.iter().enumerate() {
+                                if !visible[index] {
+                                    continue;
+                                }
+                                let is_current = index == current_idx;
+
+                                let response = if is_current {
+                                    // Current screenshot with yellow outline
+                                    ui.scope(|ui| {
+                                        ui.visuals_mut().widgets.noninteractive.bg_stroke.color =
+                                            eframe::egui::Color32::YELLOW;
+                                        ui.visuals_mut().widgets.noninteractive.bg_stroke.width =
+                                            2.0;
+                                        ui.group(|ui| {
+                                            ui.vertical(|ui| {
+                                                // Thumbnail at the top
+                                                if let Ok(img_lock) =
+                                                    entry.texture_handle.try_lock()
+                                                {
+                                                    if let Some(tex) = &*img_lock {
+                                                        let max_size = THUMBNAIL_SIZE;
+                                                        let tex_size = tex.size_vec2();
+                                                        let scale = (max_size
+                                                            / tex_size.x.max(tex_size.y))
+                                                        .min(1.0);
+                                                        let display_size = tex_size * scale;
+
+                                                        ui.image((tex.id(), display_size));
+                                                    } else {
+                                                        ui.add_space(THUMBNAIL_SIZE);
+                                                        ui.label("🖼");
+                                                    }
+                                                } else {
+                                                    ui.add_space(THUMBNAIL_SIZE);
+                                                    ui.label("🖼");
+                                                }
+
+                                                // All labels below in horizontal layout
+                                                ui.horizontal(|ui| {
+                                                    // Screenshot number
+                                                    ui.label(
+                                                        eframe::egui::RichText::new(format!(
+                                                            "#{}",
+                                                            index + 1
+                                                        ))
+                                                        .strong()
+                                                        .small(),
+                                                    );
+
+                                                    // Selection indicator
+                                                    if self
+                                                        .selection
+                                                        .contains(entry.filename.as_str())
+                                                    {
+                                                        ui.colored_label(
+                                                            eframe::egui::Color32::LIGHT_BLUE,
+                                                            "☑",
+                                                        );
+                                                    }
+
+                                                    // Rating indicator
+                                                    if let Some(rating) =
+                                                        rating_label(ratings[index])
+                                                    {
+                                                        ui.label(rating);
+                                                    }
+
+                                                    // Burst frame indicator
+                                                    if let Some((frame, frames)) =
+                                                        burst_position(&entries, index)
+                                                    {
+                                                        ui.colored_label(
+                                                            eframe::egui::Color32::LIGHT_BLUE,
+                                                            format!("🎞 {}/{}", frame, frames),
+                                                        );
+                                                    }
+
+                                                    // File location indicator
+                                                    if let Ok(location) =
+                                                        entry.file_location.try_lock()
+                                                    {
+                                                        match *location {
+                                                            crate::FileLocation::Local => {
+                                                                ui.colored_label(
+                                                                    eframe::egui::Color32::GREEN,
+                                                                    "📂",
+                                                                );
+                                                            }
+                                                            crate::FileLocation::Trash => {
+                                                                ui.colored_label(
+                                                                    eframe::egui::Color32::RED,
+                                                                    "🗑",
+                                                                );
+                                                            }
+                                                        }
+                                                    }
+
+                                                    // Compact status
+                                                    if let Ok(state) = entry.state.try_lock() {
+                                                        if state.failed {
+                                                            ui.colored_label(
+                                                                eframe::egui::Color32::RED,
+                                                                "❌",
+                                                            );
+                                                        } else if state.capturing {
+                                                            ui.colored_label(
+                                                                eframe::egui::Color32::YELLOW,
+                                                                "📸",
+                                                            );
+                                                        } else if state.moving {
+                                                            ui.colored_label(
+                                                                eframe::egui::Color32::BLUE,
+                                                                "📦",
+                                                            );
+                                                        } else if state.writing {
+                                                            ui.colored_label(
+                                                                eframe::egui::Color32::YELLOW,
+                                                                "💾",
+                                                            );
+                                                        } else if state.loading {
+                                                            ui.colored_label(
+                                                                eframe::egui::Color32::YELLOW,
+                                                                "⏳",
+                                                            );
+                                                        } else {
+                                                            ui.colored_label(
+                                                                eframe::egui::Color32::GREEN,
+                                                                "✅",
+                                                            );
+                                                        }
+                                                    }
+
+                                                    // Current indicator as the last label
+                                                    if is_current {
+                                                        ui.colored_label(
+                                                            eframe::egui::Color32::YELLOW,
+                                                            "▶",
+                                                        );
+                                                    }
+                                                });
+                                            });
+                                        })
+                                    })
+                                    .inner
+                                } else {
+                                    // Regular screenshot with default outline
+                                    ui.group(|ui| {
+                                        ui.vertical(|ui| {
+                                            // Thumbnail at the top
+                                            if let Ok(img_lock) = entry.texture_handle.try_lock() {
+                                                if let Some(tex) = &*img_lock {
+                                                    let max_size = THUMBNAIL_SIZE;
+                                                    let tex_size = tex.size_vec2();
+                                                    let scale = (max_size
+                                                        / tex_size.x.max(tex_size.y))
+                                                    .min(1.0);
+                                                    let display_size = tex_size * scale;
+
+                                                    ui.image((tex.id(), display_size));
+                                                } else {
+                                                    ui.add_space(THUMBNAIL_SIZE);
+                                                    ui.label("🖼");
+                                                }
+                                            } else {
+                                                ui.add_space(THUMBNAIL_SIZE);
+                                                ui.label("🖼");
+                                            }
+
+                                            // All labels below in horizontal layout
+                                            ui.horizontal(|ui| {
+                                                // Screenshot number
+                                                ui.label(
+                                                    eframe::egui::RichText::new(format!(
+                                                        "#{}",
+                                                        index + 1
+                                                    ))
+                                                    .strong()
+                                                    .small(),
+                                                );
+
+                                                // Selection indicator
+                                                if self.selection.contains(entry.filename.as_str())
+                                                {
+                                                    ui.colored_label(
+                                                        eframe::egui::Color32::LIGHT_BLUE,
+                                                        "☑",
+                                                    );
+                                                }
+
+                                                // Rating indicator
+                                                if let Some(rating) = rating_label(ratings[index]) {
+                                                    ui.label(rating);
+                                                }
+
+                                                // Burst frame indicator
+                                                if let Some((frame, frames)) =
+                                                    burst_position(&entries, index)
+                                                {
+                                                    ui.colored_label(
+                                                        eframe::egui::Color32::LIGHT_BLUE,
+                                                        format!("🎞 {}/{}", frame, frames),
+                                                    );
+                                                }
+
+                                                // File location indicator
+                                                if let Ok(location) = entry.file_location.try_lock()
+                                                {
+                                                    match *location {
+                                                        crate::FileLocation::Local => {
+                                                            ui.colored_label(
+                                                                eframe::egui::Color32::GREEN,
+                                                                "📂",
+                                                            );
+                                                        }
+                                                        crate::FileLocation::Trash => {
+                                                            ui.colored_label(
+                                                                eframe::egui::Color32::RED,
+                                                                "🗑",
+                                                            );
+                                                        }
+                                                    }
+                                                }
+
+                                                // Compact status
+                                                if let Ok(state) = entry.state.try_lock() {
+                                                    if state.failed {
+                                                        ui.colored_label(
+                                                            eframe::egui::Color32::RED,
+                                                            "❌",
+                                                        );
+                                                    } else if state.capturing {
+                                                        ui.colored_label(
+                                                            eframe::egui::Color32::YELLOW,
+                                                            "📸",
+                                                        );
+                                                    } else if state.moving {
+                                                        ui.colored_label(
+                                                            eframe::egui::Color32::BLUE,
+                                                            "📦",
+                                                        );
+                                                    } else if state.writing {
+                                                        ui.colored_label(
+                                                            eframe::egui::Color32::YELLOW,
+                                                            "💾",
+                                                        );
+                                                    } else if state.loading {
+                                                        ui.colored_label(
+                                                            eframe::egui::Color32::YELLOW,
+                                                            "⏳",
+                                                        );
+                                                    } else {
+                                                        ui.colored_label(
+                                                            eframe::egui::Color32::GREEN,
+                                                            "✅",
+                                                        );
+                                                    }
+                                                }
+
+                                                // Current indicator as the last label
+                                                if is_current {
+                                                    ui.colored_label(
+                                                        eframe::egui::Color32::YELLOW,
+                                                        "▶",
+                                                    );
+                                                }
+                                            });
+                                        });
+                                    })
+                                };
+
+                                // Click to show, ctrl/shift-click to select
+                                if response
+                                    .response
+                                    .interact(eframe::egui::Sense::click())
+                                    .clicked()
+                                {
+                                    clicked_thumbnail = Some((index, ctx.input(|i| i.modifiers)));
+                                }
+
+                                // Scroll to current item
+                                if is_current {
+                                    ui.scroll_to_rect(
+                                        response.response.rect,
+                                        Some(eframe::egui::Align::Center),
+                                    );
+                                }
+
+                                ui.add_space(THUMBNAIL_SPACING);
+                            }
+                        });
+                    });
+                });
+            }
+        });
+
+        if let Some((index, modifiers)) = clicked_thumbnail {
+            self.click_thumbnail(ctx, &entries, &visible, index, modifiers);
+        }
+
+        // Empty trash confirmation
+        if self.confirm_empty_trash {
+            let trashed_count = trashed_count(&entries);
+            eframe::egui::Window::new("🔥 Empty Trash")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(
+                    eframe::egui::Align2::CENTER_CENTER,
+                    eframe::egui::Vec2::ZERO,
+                )
+                .show(ctx, |ui| {
+                    ui.label(match self.rayshot_state.config.trash_backend {
+                        crate::config::TrashBackend::Folder => format!(
+                            "Permanently delete {} trashed screenshot(s) and everything else in '{}'?",
+                            trashed_count,
+                            self.rayshot_state.config.trash_dir.display()
+                        ),
+                        crate::config::TrashBackend::Freedesktop => format!(
+                            "Permanently delete {} trashed screenshot(s) from the desktop trash?",
+                            trashed_count
+                        ),
+                    });
+                    ui.label("This cannot be undone.");
+                    ui.add_space(SCREENSHOT_INFO_SPACING);
+                    ui.horizontal(|ui| {
+                        if ui.button("Delete").clicked() {
+                            self.confirm_empty_trash = false;
+                            let rayshot_state = self.rayshot_state.clone();
+                            let egui_ctx = ctx.clone();
+                            tokio::task::spawn(async move {
+                                crate::storage::empty_trash(&rayshot_state, &egui_ctx).await;
+                            });
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.confirm_empty_trash = false;
+                        }
+                    });
+                });
+        }
+
+        // Error window (if there are errors)
+        if !errors.is_empty() {
+            eframe::egui::Window::new("⚠ Errors")
+                .collapsible(true)
+                .resizable(true)
+                .default_width(ERROR_WINDOW_DEFAULT_WIDTH)
+                .show(ctx, |ui| {
+                    ui.label(format!("Found {} error(s):", errors.len()));
+                    ui.separator();
+
+                    eframe::egui::ScrollArea::vertical().show(ui, |ui| {
+                        for (i, err) in errors.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("{}.", i + 1));
+                                ui.colored_label(eframe::egui::Color32::RED, err);
+                            });
+                            ui.add_space(ERROR_LIST_ITEM_SPACING);
+                        }
+                    });
+
+                    ui.separator();
+                    if ui.button("Clear All Errors").clicked() {
+                        if let Ok(mut errors_guard) = self.rayshot_state.error_messages.try_lock() {
+                            errors_guard.clear();
+                        }
+                    }
+                });
+        }
+    }
+}
@@ -0,0 +1,69 @@
+// drives the capture → save → scan pipeline headless through `FakeCaptureSource`
+
+fn test_config(name: &str) -> rayshot::config::RayshotConfig {
+    let root = std::env::temp_dir().join(format!("rayshot-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    rayshot::config::RayshotConfig {
+        capture_backend: rayshot::config::CaptureBackend::Fake,
+        screenshot_dir: root.join("screenshots"),
+        trash_dir: root.join("trashed"),
+        ..Default::default()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn captures_are_saved_and_scanned_back_and_failures_leave_nothing() {
+    let config = test_config("fake-capture");
+    let root = config.screenshot_dir.parent().unwrap().to_path_buf();
+    // the default profile captures the window titled 原神
+    let source = std::sync::Arc::new(
+        rayshot::capture::FakeCaptureSource::new().with_window("原神", "YuanShen", 64, 48),
+    );
+    let rayshot_state = rayshot::RayshotState::new(config.clone(), source.clone());
+    let egui_ctx = eframe::egui::Context::default();
+
+    rayshot::capture::capture_screenshot(rayshot_state.clone(), egui_ctx.clone(), 0).await;
+    source.script_capture(rayshot::capture::FakeCaptureOutcome::Fail(
+        "scripted failure".to_string(),
+    ));
+    rayshot::capture::capture_screenshot(rayshot_state.clone(), egui_ctx.clone(), 0).await;
+    assert_eq!(source.capture_count(), 2);
+
+    let errors = rayshot_state.error_messages.lock().await.clone();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].contains("scripted failure"), "{}", errors[0]);
+    let entries = rayshot_state.screenshot_entries.lock().await.clone();
+    assert_eq!(entries.len(), 2);
+    let saved = entries[0].filename.to_string();
+    assert!(!entries[0].state.lock().await.failed);
+    assert!(entries[1].state.lock().await.failed);
+
+    // only the successful capture is on disk, the failed one's placeholder is gone
+    let scan_config = config.clone();
+    let scanned: Vec<_> = tokio::task::spawn_blocking(move || {
+        rayshot::storage::scan_screenshot_dir(&scan_config, rayshot::FileLocation::Local)
+    })
+    .await
+    .unwrap()
+    .unwrap()
+    .iter()
+    .map(|(_, entry)| entry.filename.to_string())
+    .collect();
+    assert_eq!(scanned, std::slice::from_ref(&saved));
+    let image = image::open(config.screenshot_dir.join(&saved))
+        .unwrap()
+        .to_rgba8();
+    assert_eq!(image.dimensions(), (64, 48));
+    assert_eq!(
+        image,
+        rayshot::capture::FakeCaptureSource::synthetic_image(64, 48, 0)
+    );
+
+    let index = rayshot::metadata::MetadataIndex::load(&config).unwrap();
+    let metadata = index.get(&saved).unwrap();
+    assert_eq!(metadata.app_name.as_deref(), Some("YuanShen"));
+    assert_eq!((metadata.width, metadata.height), (Some(64), Some(48)));
+    assert!(index.get(entries[1].filename.as_str()).is_none());
+
+    std::fs::remove_dir_all(&root).unwrap();
+}